@@ -0,0 +1,72 @@
+//! A cache of downloaded module tarballs on the local disk.
+
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+};
+
+use tokio::fs;
+
+/// The name of the directory tarballs are cached in, inside of the user's cache directory.
+const CACHE_DIR_NAME: &str = "deno_doc_info_generator";
+
+/// Stores downloaded tarballs at `{root}/{module}/{version}.tar.gz` so they don't need to be
+/// downloaded again.
+#[derive(Debug, Clone)]
+pub struct TarballCache {
+    root: PathBuf,
+}
+
+impl TarballCache {
+    /// Creates a cache storing tarballs in the directory.
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { root: root.into() }
+    }
+
+    /// Creates a cache in `~/.cache/deno_doc_info_generator`, or in `$XDG_CACHE_HOME` if it's set.
+    ///
+    /// Returns [None] if the home directory can't be found.
+    pub fn from_env() -> Option<Self> {
+        let cache_home = match env::var_os("XDG_CACHE_HOME") {
+            Some(cache_home) => PathBuf::from(cache_home),
+            None => Path::new(&env::var_os("HOME")?).join(".cache"),
+        };
+
+        Some(Self::new(cache_home.join(CACHE_DIR_NAME)))
+    }
+
+    /// Gets the path the tarball of the version of the module is stored at.
+    pub fn path(&self, module_name: &str, version: &str) -> PathBuf {
+        self.root
+            .join(module_name)
+            .join(format!("{}.tar.gz", version))
+    }
+
+    /// Reads the cached tarball of the version of the module, if it's been cached.
+    pub async fn get(&self, module_name: &str, version: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(module_name, version)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stores the tarball of the version of the module.
+    ///
+    /// The tarball is written to a temporary file that's then renamed, so an interrupted write
+    /// can't leave a corrupt tarball in the cache.
+    pub async fn put(&self, module_name: &str, version: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(module_name, version);
+        let tmp_path = path.with_extension("gz.tmp");
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+
+        fs::write(&tmp_path, bytes).await?;
+        fs::rename(&tmp_path, &path).await
+    }
+}
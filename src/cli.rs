@@ -0,0 +1,337 @@
+use std::{
+    env,
+    io::{self, Write},
+    iter,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use deno_doc::DocNodeKind;
+use deno_doc_info_generator::DEFAULT_ENTRY;
+use swc_ecmascript::parser::TsConfig;
+use thiserror::Error;
+
+use crate::output::{OutputFormat, OutputOptions};
+
+/// The version of a module that's used if one isn't provided.
+pub const LATEST_VERSION: &str = "latest";
+
+/// The maximum size of an archive in megabytes if `--max-archive-size` isn't provided.
+const DEFAULT_MAX_ARCHIVE_SIZE: u64 = 100;
+
+/// How many modules from `--modules-file` are processed at once if `--concurrency` isn't provided.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// The name of the binary, used for usage messages and completion scripts.
+const BINARY_NAME: &str = "deno_doc_info_generator";
+
+/// Options for the generator parsed from the command line.
+#[derive(Debug, Parser)]
+#[command(name = BINARY_NAME)]
+pub struct Options {
+    /// The name of the module docs are generated for.
+    pub module: Option<String>,
+    /// The version of the module, which is the latest version if it isn't provided.
+    pub version: Option<String>,
+    /// The format the parsed doc nodes should be written in, which is JSON if it isn't provided.
+    #[arg(long, visible_alias = "format")]
+    pub output: Option<OutputFormat>,
+    /// The file the output is written to instead of stdout.
+    #[arg(short = 'o', long)]
+    pub output_file: Option<PathBuf>,
+    /// Options that only apply to some of the output formats.
+    #[command(flatten)]
+    pub output_options: OutputOptions,
+    /// The files docs are parsed from, relative to the root directory of the module.
+    #[arg(long = "entry")]
+    pub entries: Vec<String>,
+    /// Prefixes of import specifiers and what they're replaced with, as `<from>=<to>`.
+    #[arg(long = "alias", value_parser = parse_alias)]
+    pub aliases: Vec<(String, String)>,
+    /// Whether the cyclomatic complexity of exported functions should be reported.
+    #[arg(long)]
+    pub analyze_complexity: bool,
+    /// The maximum size in megabytes of a downloaded or decompressed archive.
+    #[arg(long)]
+    pub max_archive_size: Option<u64>,
+    /// Whether statistics about loading the module should be reported.
+    #[arg(long)]
+    pub stats: bool,
+    /// Symbols that have to be exported by the module, otherwise the process exits with an error.
+    #[arg(long = "assert-symbol")]
+    pub assert_symbols: Vec<String>,
+    /// Whether the process should exit with an error if no doc nodes were parsed.
+    #[arg(long)]
+    pub fail_on_empty: bool,
+    /// Whether the process should exit with an error if any symbol is deprecated.
+    #[arg(long)]
+    pub assert_no_deprecated: bool,
+    /// Whether the duration of each instrumented operation should be logged.
+    #[arg(long)]
+    pub trace: bool,
+    /// Whether doc nodes for symbols defined in remote modules should be left out.
+    #[arg(long)]
+    pub skip_external: bool,
+    /// Whether symbols that aren't exported should be parsed too.
+    #[arg(long)]
+    pub private: bool,
+    /// Whether decorators are parsed in every file.
+    #[arg(long)]
+    pub decorators: bool,
+    /// Whether early errors aren't reported when parsing files.
+    #[arg(long)]
+    pub no_early_errors: bool,
+    /// The only kind of symbol that should be included, besides the module doc that every format
+    /// gets the module description from.
+    #[arg(long, value_parser = parse_kind)]
+    pub kind: Option<DocNodeKind>,
+    /// The interface classes have to implement to be included, besides the module doc.
+    #[arg(long)]
+    pub implements: Option<String>,
+    /// The directory downloaded responses are cached in.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+    /// Whether downloaded tarballs shouldn't be read from or stored in the tarball cache.
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Whether a Deno export map for the archive should be written instead of doc nodes.
+    #[arg(long)]
+    pub export_map: bool,
+    /// Whether the archive should be read from stdin instead of being downloaded.
+    #[arg(long)]
+    pub stdin: bool,
+    /// Whether the TypeScript files in the archive should be listed instead of generating docs.
+    #[arg(long)]
+    pub list_files: bool,
+    /// Whether the published versions of the module should be listed instead of generating docs.
+    #[arg(long)]
+    pub list_versions: bool,
+    /// Whether `npm:` imports are resolved using the npm registry.
+    #[arg(long)]
+    pub allow_npm: bool,
+    /// The directory of a project on disk that should be parsed instead of downloading a module.
+    #[arg(long)]
+    pub local: Option<PathBuf>,
+    /// The two versions whose exported symbols should be compared instead of generating docs.
+    #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+    pub diff: Option<Vec<String>>,
+    /// The earliest a version can have been published to be used.
+    #[arg(long, value_parser = parse_date)]
+    pub since: Option<DateTime<Utc>>,
+    /// The latest a version can have been published to be used.
+    #[arg(long, value_parser = parse_date)]
+    pub until: Option<DateTime<Utc>>,
+    /// The file in the archive that should be written to stdout instead of generating docs.
+    #[arg(long)]
+    pub cat: Option<String>,
+    /// The directory the files in the archive should be written to instead of generating docs.
+    #[arg(long)]
+    pub extract: Option<PathBuf>,
+    /// A file listing `module[@version]` entries to generate docs for, one per line.
+    #[arg(long)]
+    pub modules_file: Option<PathBuf>,
+    /// How many modules from the modules file are processed at once.
+    #[arg(long)]
+    pub concurrency: Option<usize>,
+    /// Whether the checksum of downloaded archives isn't verified against their metadata.
+    #[arg(long)]
+    pub skip_verify: bool,
+    /// Only modules from the modules file with this tag in the registry have docs generated.
+    #[arg(long)]
+    pub filter_tag: Option<String>,
+    /// The path a Chrome trace of loading the files of the module should be written to.
+    #[arg(long)]
+    pub profile_output: Option<PathBuf>,
+    /// The path the downloaded tarball of the module is saved to.
+    #[arg(long)]
+    pub save_tarball: Option<PathBuf>,
+    /// The tarball that should be uploaded to the registry instead of generating docs.
+    #[arg(long)]
+    pub upload: Option<PathBuf>,
+    /// The URL of the self-hosted registry modules are uploaded to.
+    #[arg(long)]
+    pub registry: Option<String>,
+    /// The shell a completion script should be written for instead of generating docs.
+    #[arg(long)]
+    pub generate_completions: Option<Shell>,
+}
+
+impl Options {
+    /// Parses the options from the arguments the process was started with, exiting with a usage
+    /// message if they aren't valid.
+    pub fn from_args() -> Self {
+        Self::parse(env::args().skip(1)).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parses the options from the provided arguments, excluding the program name.
+    pub fn parse<I>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Self::try_parse_from(iter::once(BINARY_NAME.to_string()).chain(args))
+    }
+
+    /// Checks if the process should exit with an error if no doc nodes were parsed, which is
+    /// always the case when symbols are asserted.
+    pub fn fails_on_empty(&self) -> bool {
+        self.fail_on_empty || !self.assert_symbols.is_empty()
+    }
+
+    /// Gets the format the output is written in, defaulting to JSON.
+    pub fn output_format(&self) -> OutputFormat {
+        self.output.unwrap_or(OutputFormat::Json)
+    }
+
+    /// Gets the files docs are parsed from, defaulting to `mod.ts`.
+    pub fn entries(&self) -> Vec<&str> {
+        if self.entries.is_empty() {
+            vec![DEFAULT_ENTRY]
+        } else {
+            self.entries.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Gets the version of the module, defaulting to the latest version.
+    pub fn version(&self) -> &str {
+        self.version.as_deref().unwrap_or(LATEST_VERSION)
+    }
+
+    /// Gets how many modules from the modules file are processed at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1)
+    }
+
+    /// Gets the parser options used for every file, before the ones needed by its extension are
+    /// enabled.
+    pub fn ts_config(&self) -> TsConfig {
+        TsConfig {
+            decorators: self.decorators,
+            no_early_errors: self.no_early_errors,
+            ..TsConfig::default()
+        }
+    }
+
+    /// Gets the maximum size in bytes of a downloaded or decompressed archive.
+    pub fn max_archive_size_bytes(&self) -> u64 {
+        self.max_archive_size
+            .unwrap_or(DEFAULT_MAX_ARCHIVE_SIZE)
+            .saturating_mul(1024 * 1024)
+    }
+}
+
+/// Writes a script completing the arguments of the generator for the shell.
+pub fn write_completions<W>(shell: Shell, mut writer: W) -> io::Result<()>
+where
+    W: Write,
+{
+    clap_complete::generate(shell, &mut Options::command(), BINARY_NAME, &mut writer);
+    writer.flush()
+}
+
+/// Parses an alias, which is the prefix it replaces and what it's replaced with joined by `=`.
+fn parse_alias(value: &str) -> Result<(String, String), CliError> {
+    let mut parts = value.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(from), Some(to)) if !from.is_empty() => Ok((from.to_string(), to.to_string())),
+        _ => Err(CliError::InvalidAlias),
+    }
+}
+
+/// Parses the kind of a symbol, named like in the JSON output.
+fn parse_kind(value: &str) -> Result<DocNodeKind, CliError> {
+    match value {
+        "function" => Ok(DocNodeKind::Function),
+        "class" => Ok(DocNodeKind::Class),
+        "interface" => Ok(DocNodeKind::Interface),
+        "variable" => Ok(DocNodeKind::Variable),
+        "typeAlias" => Ok(DocNodeKind::TypeAlias),
+        "enum" => Ok(DocNodeKind::Enum),
+        "namespace" => Ok(DocNodeKind::Namespace),
+        _ => Err(CliError::UnknownKind),
+    }
+}
+
+/// Parses a date, which is either an RFC 3339 timestamp or a `YYYY-MM-DD` date that's taken as
+/// midnight UTC.
+fn parse_date(value: &str) -> Result<DateTime<Utc>, CliError> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => Ok(DateTime::from_utc(date.and_hms(0, 0, 0), Utc)),
+        Err(_) => Err(CliError::InvalidDate),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("expected <from>=<to>")]
+    InvalidAlias,
+    #[error("expected function, class, interface, variable, typeAlias, enum or namespace")]
+    UnknownKind,
+    #[error("expected an RFC 3339 timestamp or a YYYY-MM-DD date")]
+    InvalidDate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Options {
+        Options::parse(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    #[test]
+    fn command_is_valid() {
+        Options::command().debug_assert();
+    }
+
+    #[test]
+    fn fail_on_empty() {
+        assert!(!parse(&["example"]).fails_on_empty());
+        assert!(parse(&["example", "--fail-on-empty"]).fails_on_empty());
+    }
+
+    #[test]
+    fn assert_symbol_fails_on_empty() {
+        assert!(parse(&["example", "--assert-symbol", "serve"]).fails_on_empty());
+    }
+
+    #[test]
+    fn ts_config() {
+        let default = parse(&["example"]).ts_config();
+        assert!(!default.decorators);
+        assert!(!default.no_early_errors);
+
+        let ts_config = parse(&["example", "--decorators", "--no-early-errors"]).ts_config();
+        assert!(ts_config.decorators);
+        assert!(ts_config.no_early_errors);
+    }
+
+    #[test]
+    fn diff_takes_two_versions() {
+        let options = parse(&["example", "--diff", "1.0.0", "2.0.0"]);
+
+        assert_eq!(options.module.as_deref(), Some("example"));
+        assert_eq!(
+            options.diff,
+            Some(vec!["1.0.0".to_string(), "2.0.0".to_string()])
+        );
+    }
+
+    #[test]
+    fn completions_include_every_flag() {
+        let mut script = Vec::new();
+        write_completions(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+
+        for arg in Options::command().get_arguments() {
+            if let Some(flag) = arg.get_long() {
+                assert!(script.contains(&format!("--{}", flag)), "--{}", flag);
+            }
+        }
+    }
+}
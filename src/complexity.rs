@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use deno_doc::{parser::DocFileLoader, DocNode};
+use swc_common::BytePos;
+use swc_ecmascript::{
+    ast::{
+        BinExpr, BinaryOp, CatchClause, CondExpr, DoWhileStmt, Expr, FnDecl, ForInStmt, ForOfStmt,
+        ForStmt, IfStmt, Pat, SwitchCase, VarDeclarator, WhileStmt,
+    },
+    parser::{Parser, StringInput, Syntax},
+    visit::{Node, Visit, VisitWith},
+};
+
+/// Computes the approximate cyclomatic complexity of every function doc node, keyed by the
+/// index of the node.
+///
+/// Functions are matched to their bodies by name in the file the node was found in, so the
+/// sources are loaded again through the loader that was used for parsing.
+pub async fn analyze(loader: &dyn DocFileLoader, nodes: &[DocNode]) -> HashMap<usize, usize> {
+    let mut files: HashMap<&str, HashMap<String, usize>> = HashMap::new();
+    let mut complexity = HashMap::new();
+
+    for (index, node) in nodes.iter().enumerate() {
+        if node.function_def.is_none() {
+            continue;
+        }
+
+        let filename = node.location.filename.as_str();
+        if !files.contains_key(filename) {
+            let functions = match loader.load_source_code(filename).await {
+                Ok((syntax, source)) => function_complexities(syntax, &source).unwrap_or_default(),
+                Err(e) => {
+                    tracing::warn!("Unable to load {} for complexity analysis: {}", filename, e);
+                    HashMap::default()
+                }
+            };
+            files.insert(filename, functions);
+        }
+
+        if let Some(value) = files[filename].get(&node.name) {
+            complexity.insert(index, *value);
+        }
+    }
+
+    complexity
+}
+
+/// Parses the source and computes the complexity of each named function in it, returning
+/// [None] if the source couldn't be parsed.
+fn function_complexities(syntax: Syntax, source: &str) -> Option<HashMap<String, usize>> {
+    let input = StringInput::new(source, BytePos(0), BytePos(source.len() as u32));
+    let module = Parser::new(syntax, input, None).parse_module().ok()?;
+
+    let mut collector = FunctionCollector::default();
+    module.visit_children_with(&mut collector);
+    Some(collector.functions)
+}
+
+/// Finds functions declared with a name, either as a declaration or a variable holding an
+/// arrow function or function expression.
+#[derive(Default)]
+struct FunctionCollector {
+    functions: HashMap<String, usize>,
+}
+
+impl Visit for FunctionCollector {
+    fn visit_fn_decl(&mut self, n: &FnDecl, _parent: &dyn Node) {
+        // Overload signatures don't have a body, so only the implementation is counted.
+        if n.function.body.is_some() {
+            let mut counter = ComplexityCounter::default();
+            n.function.visit_children_with(&mut counter);
+            self.functions
+                .entry(n.ident.sym.to_string())
+                .or_insert(counter.complexity());
+        }
+
+        n.visit_children_with(self);
+    }
+
+    fn visit_var_declarator(&mut self, n: &VarDeclarator, _parent: &dyn Node) {
+        if let (Pat::Ident(binding), Some(init)) = (&n.name, &n.init) {
+            if let Expr::Arrow(_) | Expr::Fn(_) = &**init {
+                let mut counter = ComplexityCounter::default();
+                init.visit_children_with(&mut counter);
+                self.functions
+                    .entry(binding.id.sym.to_string())
+                    .or_insert(counter.complexity());
+            }
+        }
+
+        n.visit_children_with(self);
+    }
+}
+
+/// Counts the branches in a function body.
+#[derive(Default)]
+struct ComplexityCounter {
+    branches: usize,
+}
+
+impl ComplexityCounter {
+    fn complexity(&self) -> usize {
+        self.branches + 1
+    }
+}
+
+impl Visit for ComplexityCounter {
+    fn visit_if_stmt(&mut self, n: &IfStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_cond_expr(&mut self, n: &CondExpr, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_for_stmt(&mut self, n: &ForStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_for_in_stmt(&mut self, n: &ForInStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_while_stmt(&mut self, n: &WhileStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_do_while_stmt(&mut self, n: &DoWhileStmt, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_switch_case(&mut self, n: &SwitchCase, _parent: &dyn Node) {
+        // The default case doesn't add a path that the other cases don't already.
+        if n.test.is_some() {
+            self.branches += 1;
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_catch_clause(&mut self, n: &CatchClause, _parent: &dyn Node) {
+        self.branches += 1;
+        n.visit_children_with(self);
+    }
+
+    fn visit_bin_expr(&mut self, n: &BinExpr, _parent: &dyn Node) {
+        if let BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing = n.op {
+            self.branches += 1;
+        }
+        n.visit_children_with(self);
+    }
+}
@@ -1,152 +1,1321 @@
 use std::{
-    collections::HashMap,
-    io::{self, Cursor, Read},
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
+    io::{self, Cursor, Read, Write},
     ops::{Deref, DerefMut},
-    path::Path,
+    path::{Component, Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use deno_doc::{parser::DocFileLoader, DocError};
-use flate2::read::GzDecoder;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use futures::future::LocalBoxFuture;
+use rayon::prelude::*;
+use reqwest::{Client, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use sha2::{Digest, Sha256};
 use swc_ecmascript::parser::{Syntax, TsConfig};
-use tar::{Archive, Entry};
-use tokio::sync::Mutex;
+use tar::{Archive, Builder, Entry, Header};
+use tokio::{fs::File, io::AsyncReadExt, sync::Mutex};
+use tracing::{Instrument, Span};
+
+use crate::{
+    fetch::{self, FetchError},
+    npm::NpmResolver,
+};
+
+/// How much of each file is scanned when estimating how hard an archive is to parse.
+const ESTIMATE_SCAN_SIZE: u64 = 256;
+
+/// How much of each file is scanned when looking for binary files.
+const BINARY_SCAN_SIZE: u64 = 512;
+
+/// The entropy in bits per byte above which data is assumed to be binary, since even dense text
+/// is well below it while compressed or compiled data is close to the maximum of 8.
+const BINARY_ENTROPY_THRESHOLD: f64 = 7.0;
+
+/// The fewest bytes the entropy of a file is measured from, since it's meaningless for tiny files.
+const MIN_ENTROPY_SAMPLE: usize = 64;
 
 /// An archive containing the files of a Deno module.
 pub struct DenoArchive {
     pub module_name: String,
     pub version: String,
-    pub archive: Archive<Cursor<Vec<u8>>>,
+    // The decompressed tar file, which is shared between clones of the archive.
+    buffer: Arc<[u8]>,
+    // The reader of the most recent pass over the entries, which is replaced by a new one reading
+    // from the start of the buffer every time the entries are read.
+    archive: Archive<Cursor<Arc<[u8]>>>,
+    // Where each entry's data is, built the first time an entry is looked up by path.
+    index: Option<HashMap<String, (u64, u64)>>,
 }
 
 impl DenoArchive {
     /// Creates a [DenoArchive] from a reader containing a tar.gz file.
     pub fn from_reader<R>(module_name: String, version: String, reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        Self::from_reader_with_limit(module_name, version, reader, u64::MAX)
+    }
+
+    /// Downloads the archive of the version of the module and decompresses it.
+    pub async fn from_url(
+        client: &Client,
+        module_name: &str,
+        version: &str,
+    ) -> Result<Self, FetchError> {
+        let metadata = fetch::fetch_version_metadata(
+            client,
+            module_name,
+            version,
+            fetch::DEFAULT_MAX_RESPONSE_SIZE,
+        )
+        .await?;
+        let url = metadata.upload_options.tarball_url()?;
+        let bytes = fetch::fetch_tarball(client, &url).await?;
+
+        if let Some(expected) = &metadata.checksum {
+            verify_sha256(&bytes, expected).map_err(|actual| FetchError::ChecksumMismatch {
+                expected: expected.clone(),
+                actual,
+            })?;
+        }
+
+        Ok(Self::from_reader(
+            module_name.to_string(),
+            version.to_string(),
+            Cursor::new(bytes),
+        )?)
+    }
+
+    /// Creates a [DenoArchive] from a tar.gz file piped through stdin.
+    pub fn from_stdin(module_name: String, version: String) -> io::Result<Self> {
+        let stdin = io::stdin();
+        let reader = stdin.lock();
+        Self::from_reader(module_name, version, reader)
+    }
+
+    /// Creates a [DenoArchive] from a reader containing a tar.gz file, erroring if the
+    /// decompressed archive is larger than `limit` bytes.
+    pub fn from_reader_with_limit<R>(
+        module_name: String,
+        version: String,
+        reader: R,
+        limit: u64,
+    ) -> io::Result<Self>
     where
         R: Read,
     {
         let mut buffer = Vec::new();
-        let mut decoder = GzDecoder::new(reader);
-        decoder.read_to_end(&mut buffer)?;
+        let decoder = GzDecoder::new(reader);
+
+        // Reads a byte past the limit so we can tell if the archive would've exceeded it.
+        decoder
+            .take(limit.saturating_add(1))
+            .read_to_end(&mut buffer)?;
+
+        if buffer.len() as u64 > limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("decompressed archive is larger than {} bytes", limit),
+            ));
+        }
+
+        let buffer: Arc<[u8]> = buffer.into();
 
         Ok(Self {
             module_name,
             version,
-            archive: Archive::new(Cursor::new(buffer)),
+            archive: Archive::new(Cursor::new(buffer.clone())),
+            buffer,
+            index: None,
         })
     }
 
+    /// Creates a new reader for the archive, reading from the start of the tar file.
+    fn fresh_archive(&mut self) -> &mut Archive<Cursor<Arc<[u8]>>> {
+        self.archive = Archive::new(Cursor::new(self.buffer.clone()));
+        &mut self.archive
+    }
+
     pub fn entries(&mut self) -> io::Result<impl Iterator<Item = io::Result<DenoEntry<'_>>>> {
         let iterator = self
-            .archive
+            .fresh_archive()
             .entries()?
             .skip(1)
             .map(|e| e.map(|e| DenoEntry(e)));
         Ok(iterator)
     }
 
+    /// Iterates over the entries that are TypeScript files.
+    pub fn iter_typescript_files(
+        &mut self,
+    ) -> io::Result<impl Iterator<Item = io::Result<DenoEntry<'_>>>> {
+        let iterator = self.entries()?.filter(|entry| match entry {
+            Ok(entry) => entry.is_typescript(),
+            // Errors are kept so callers know an entry couldn't be read.
+            Err(_) => true,
+        });
+
+        Ok(iterator)
+    }
+
+    /// Lists the paths of every `.ts` and `.tsx` file in the archive.
+    pub fn list_typescript_files(&mut self) -> io::Result<Vec<String>> {
+        let mut paths = Vec::new();
+
+        for entry in self.iter_typescript_files()? {
+            let entry = entry?;
+            if entry.header().entry_type().is_file() {
+                paths.push(entry.path()?.to_string_lossy().into_owned());
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Lists the path of every directory containing a file in the archive, sorted.
+    ///
+    /// The directories are taken from the paths of the files, since archives don't always have
+    /// entries for them.
+    pub fn list_directories(&mut self) -> io::Result<Vec<String>> {
+        let mut directories = BTreeSet::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?;
+            for ancestor in path.ancestors().skip(1) {
+                if ancestor.as_os_str().is_empty() {
+                    continue;
+                }
+
+                // Every ancestor of a directory that was already added has been added too.
+                if !directories.insert(ancestor.to_string_lossy().into_owned()) {
+                    break;
+                }
+            }
+        }
+
+        Ok(directories.into_iter().collect())
+    }
+
+    /// Reads every file in the archive, decoding their contents in parallel, sorted by path.
+    ///
+    /// The archive has to be read sequentially, so only decoding the files is parallelized.
+    pub fn entries_parallel(&mut self) -> io::Result<Vec<OwnedDenoEntry>> {
+        let files = self.read_files()?;
+
+        let mut entries = files
+            .into_par_iter()
+            .map(|(path, bytes)| {
+                let content = String::from_utf8(bytes)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                Ok(OwnedDenoEntry { path, content })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    /// Reads every file in the archive, keyed by its path relative to the root directory.
+    ///
+    /// Files that aren't valid UTF-8 are skipped.
+    pub fn into_flat_map(mut self) -> io::Result<HashMap<String, String>> {
+        let mut files = HashMap::new();
+
+        for (path, bytes) in self.read_files()? {
+            // Every file is inside of the root directory.
+            let relative_path = path.splitn(2, '/').nth(1).unwrap_or(&path).to_string();
+
+            match String::from_utf8(bytes) {
+                Ok(content) => {
+                    files.insert(relative_path, content);
+                }
+                Err(_) => tracing::warn!("Skipping {}, which isn't valid UTF-8", path),
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn read_files(&mut self) -> io::Result<Vec<(String, Vec<u8>)>> {
+        let mut files = Vec::new();
+
+        for entry in self.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let mut bytes = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut bytes)?;
+            files.push((path, bytes));
+        }
+
+        Ok(files)
+    }
+
+    /// Iterates over the entries that come after the entry at `start_after`, so interrupted work
+    /// can resume from the last entry that was processed.
+    ///
+    /// Nothing is yielded if no entry has the path.
+    pub fn entries_from(
+        &mut self,
+        start_after: &str,
+    ) -> io::Result<impl Iterator<Item = io::Result<DenoEntry<'_>>>> {
+        let start_after = PathBuf::from(start_after);
+        let mut found = false;
+
+        let iterator = self.entries()?.filter(move |entry| {
+            if found {
+                return true;
+            }
+
+            // Entries before the checkpoint were already handled, so errors reading them are
+            // skipped along with them.
+            if let Ok(entry) = entry {
+                found = entry
+                    .path()
+                    .map(|path| path.as_ref() == start_after.as_path())
+                    .unwrap_or(false);
+            }
+
+            false
+        });
+
+        Ok(iterator)
+    }
+
+    /// Estimates how hard the archive will be to parse from the names of its TypeScript files and
+    /// the first 256 bytes of each, without parsing anything.
+    pub fn estimate_parsed_complexity(&mut self) -> io::Result<ComplexityEstimate> {
+        let mut estimate = ComplexityEstimate::default();
+        let mut total_size = 0;
+
+        for entry in self.iter_typescript_files()? {
+            let mut entry = entry?;
+            estimate.typescript_files += 1;
+            total_size += entry.size();
+
+            if entry.path()?.extension().and_then(|ext| ext.to_str()) == Some("tsx") {
+                estimate.has_tsx = true;
+            }
+
+            let mut start = Vec::with_capacity(ESTIMATE_SCAN_SIZE as usize);
+            entry
+                .by_ref()
+                .take(ESTIMATE_SCAN_SIZE)
+                .read_to_end(&mut start)?;
+            if String::from_utf8_lossy(&start)
+                .lines()
+                .any(|line| line.trim_start().starts_with('@'))
+            {
+                estimate.has_decorators = true;
+            }
+        }
+
+        if estimate.typescript_files > 0 {
+            estimate.avg_file_size = total_size / estimate.typescript_files as u64;
+        }
+
+        Ok(estimate)
+    }
+
+    /// Counts the lines of every TypeScript file in the archive, classifying each as code, a
+    /// comment or blank.
+    ///
+    /// Lines with code followed by a comment are counted as code.
+    pub fn count_lines_of_code(&mut self) -> io::Result<LinesOfCode> {
+        let mut lines = LinesOfCode::default();
+
+        for entry in self.iter_typescript_files()? {
+            let mut entry = entry?;
+            let mut source = Vec::new();
+            entry.read_to_end(&mut source)?;
+
+            let mut in_block_comment = false;
+            for line in String::from_utf8_lossy(&source).split('\n') {
+                let line = line.trim();
+                lines.total += 1;
+
+                if in_block_comment {
+                    lines.comment += 1;
+                    in_block_comment = !line.contains("*/");
+                } else if line.is_empty() {
+                    lines.blank += 1;
+                } else if line.starts_with("//") {
+                    lines.comment += 1;
+                } else if let Some(comment) = line.strip_prefix("/*") {
+                    lines.comment += 1;
+                    in_block_comment = !comment.contains("*/");
+                } else {
+                    lines.code += 1;
+                }
+            }
+        }
+
+        Ok(lines)
+    }
+
+    /// Finds the entries that are symbolic links along with the paths they point to.
+    pub fn symlink_entries(&mut self) -> io::Result<Vec<SymlinkEntry>> {
+        let mut symlinks = Vec::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_symlink() {
+                continue;
+            }
+
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let target = match entry.link_name()? {
+                Some(target) => target.to_string_lossy().into_owned(),
+                None => continue,
+            };
+
+            symlinks.push(SymlinkEntry { path, target });
+        }
+
+        Ok(symlinks)
+    }
+
+    /// Writes every file in the archive under `dest`, with the root directory of the archive
+    /// stripped from their paths.
+    ///
+    /// Symlinks and other entries that aren't files or directories are skipped, as are entries
+    /// whose paths would end up outside of `dest`. On unix files keep the mode from their header.
+    pub fn extract_to_dir(&mut self, dest: &Path) -> io::Result<()> {
+        for entry in self.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let entry_type = entry.header().entry_type();
+
+            // Every entry is inside of the root directory.
+            let relative_path: PathBuf = path.components().skip(1).collect();
+            if relative_path.as_os_str().is_empty() {
+                continue;
+            }
+
+            if !relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                tracing::warn!(
+                    "Skipping {}, which is outside of the archive",
+                    path.display()
+                );
+                continue;
+            }
+
+            let target = dest.join(&relative_path);
+            if entry_type.is_dir() {
+                fs::create_dir_all(&target)?;
+            } else if entry_type.is_file() {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(&mut entry, &mut fs::File::create(&target)?)?;
+                set_mode(&target, entry.permissions()?)?;
+            } else {
+                tracing::warn!("Skipping {}, which isn't a regular file", path.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts the files in the archive, returning how many there are and their total size in
+    /// bytes.
+    pub fn file_totals(&mut self) -> io::Result<(usize, u64)> {
+        let mut count = 0;
+        let mut total_bytes = 0;
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            if entry.header().entry_type().is_file() {
+                count += 1;
+                total_bytes += entry.size();
+            }
+        }
+
+        Ok((count, total_bytes))
+    }
+
+    /// Finds the files that look like they're binary, which would fail to load as source code.
+    ///
+    /// A file is considered binary if the first 512 bytes contain a null byte or have a high
+    /// entropy.
+    pub fn contains_binary_files(&mut self) -> io::Result<Vec<String>> {
+        let mut paths = Vec::new();
+
+        for entry in self.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let mut start = Vec::with_capacity(BINARY_SCAN_SIZE as usize);
+            entry
+                .by_ref()
+                .take(BINARY_SCAN_SIZE)
+                .read_to_end(&mut start)?;
+
+            let is_binary = start.contains(&0)
+                || (start.len() >= MIN_ENTROPY_SAMPLE
+                    && entropy(&start) > BINARY_ENTROPY_THRESHOLD);
+
+            if is_binary {
+                paths.push(entry.path()?.to_string_lossy().into_owned());
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Applies the transform to the contents of every file in the archive, returning how many of
+    /// the files were changed by it.
+    ///
+    /// Files that aren't valid UTF-8 are left as they are.
+    pub fn patch_all<F>(&mut self, transform: F) -> io::Result<usize>
+    where
+        F: Fn(String) -> String,
+    {
+        let mut builder = Builder::new(Vec::new());
+        let mut patched = 0;
+
+        for (index, entry) in self.fresh_archive().entries()?.enumerate() {
+            let mut entry = entry?;
+            let mut header = entry.header().clone();
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut data)?;
+
+            // The global header isn't a file, so it's copied over as it is.
+            if index == 0 {
+                builder.append(&header, data.as_slice())?;
+                continue;
+            }
+
+            if header.entry_type().is_file() {
+                data = match String::from_utf8(data) {
+                    Ok(source) => {
+                        let transformed = transform(source.clone());
+                        if transformed != source {
+                            patched += 1;
+                        }
+                        transformed.into_bytes()
+                    }
+                    Err(e) => e.into_bytes(),
+                };
+            }
+
+            let path = entry.path()?.into_owned();
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, path, data.as_slice())?;
+        }
+
+        self.buffer = builder.into_inner()?.into();
+        // The offsets of the files will have moved if any of their sizes changed.
+        self.index = None;
+
+        Ok(patched)
+    }
+
+    /// Combines two archives into a new one containing the entries of both, preferring the
+    /// entries of `b` when both archives contain the same path.
+    pub fn merge(mut a: DenoArchive, mut b: DenoArchive) -> io::Result<DenoArchive> {
+        // The first entry is the global header which isn't exposed as a file, so it's kept from
+        // `a` to keep the layout that the rest of the archive code expects.
+        let global_header = match a.fresh_archive().entries()?.next() {
+            Some(entry) => {
+                let mut entry = entry?;
+                let mut data = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut data)?;
+                Some((entry.header().clone(), data))
+            }
+            None => None,
+        };
+
+        let mut files: Vec<(PathBuf, Header, Vec<u8>)> = Vec::new();
+        let mut indices: HashMap<PathBuf, usize> = HashMap::new();
+
+        for archive in [&mut a, &mut b].iter_mut() {
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let path = entry.path()?.into_owned();
+                let mut data = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut data)?;
+                let file = (path.clone(), entry.header().clone(), data);
+
+                match indices.get(&path) {
+                    Some(&index) => files[index] = file,
+                    None => {
+                        indices.insert(path, files.len());
+                        files.push(file);
+                    }
+                }
+            }
+        }
+
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        if let Some((header, data)) = global_header {
+            builder.append(&header, data.as_slice())?;
+        }
+
+        for (path, mut header, data) in files {
+            builder.append_data(&mut header, path, data.as_slice())?;
+        }
+
+        let bytes = builder.into_inner()?.finish()?;
+
+        Self::from_reader(
+            format!("{}+{}", a.module_name, b.module_name),
+            format!("{}+{}", a.version, b.version),
+            Cursor::new(bytes),
+        )
+    }
+
     /// Gets the root directory in the archive.
     pub fn root_directory(&mut self) -> io::Result<Option<String>> {
-        let ret = match self.archive.entries()?.skip(1).next() {
+        match self.fresh_archive().entries()?.nth(1) {
             Some(res) => {
                 let entry = res?;
                 Ok(entry.path()?.to_str().map(String::from))
             }
             None => Ok(None),
+        }
+    }
+
+    /// Gets the name of the root directory without its trailing slash or its `-version` or
+    /// `@version` suffix, so `channo-0.1.1/` becomes `channo`.
+    ///
+    /// The version is matched with and without a leading `v`, and the name is left as it is if it
+    /// doesn't end with the version.
+    pub fn root_module_name(&mut self) -> io::Result<Option<String>> {
+        let root_directory = match self.root_directory()? {
+            Some(root_directory) => root_directory,
+            None => return Ok(None),
         };
+        let name = root_directory.trim_end_matches('/');
 
-        replace_with::replace_with_or_abort(&mut self.archive, |archive| {
-            let mut reader = archive.into_inner();
+        let versions = [self.version.as_str(), self.version.trim_start_matches('v')];
+        let module_name = versions
+            .iter()
+            .filter(|version| !version.is_empty())
+            .flat_map(|version| {
+                let name = name.strip_suffix(version);
+                name.and_then(|name| name.strip_suffix('-').or_else(|| name.strip_suffix('@')))
+            })
+            .next()
+            .unwrap_or(name);
 
-            // Rewinds the reader so we can read it again.
-            reader.set_position(0);
+        Ok(Some(module_name.to_string()))
+    }
 
-            Archive::new(reader)
-        });
+    /// Scans the archive once for where each entry's data is in the decompressed archive, as its
+    /// byte offset and length keyed by the path of the entry.
+    pub fn index(&mut self) -> io::Result<HashMap<String, (u64, u64)>> {
+        let mut index = HashMap::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+            if let Some(path) = entry.path()?.to_str() {
+                index.insert(path.to_string(), (entry.raw_file_position(), entry.size()));
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Scans the archive for the byte offset of each entry's data, keyed by the path of the
+    /// entry.
+    pub fn sparse_index(&mut self) -> io::Result<HashMap<String, u64>> {
+        let index = self.index()?;
+        Ok(index
+            .into_iter()
+            .map(|(path, (offset, _))| (path, offset))
+            .collect())
+    }
+
+    /// Reads the contents of the entry at the path, seeking straight to it using the
+    /// [index](DenoArchive::index) instead of scanning the archive.
+    pub fn entry_by_path(&mut self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        if self.index.is_none() {
+            self.index = Some(self.index()?);
+        }
+
+        let (offset, length) = match self.index.as_ref().and_then(|index| index.get(path)) {
+            Some(&(offset, length)) => (offset as usize, length as usize),
+            None => return Ok(None),
+        };
+
+        let data = self
+            .buffer
+            .get(offset..offset + length)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "entry is out of bounds"))?;
+        Ok(Some(data.to_vec()))
+    }
+
+    /// Writes the contents of the file at the path to stdout, like the `cat` command.
+    pub fn cat(&mut self, path: &str) -> io::Result<()> {
+        let bytes = self.entry_by_path(path)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} not in archive", path))
+        })?;
 
-        ret
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(&bytes)?;
+        stdout.flush()
     }
+
+    /// Reads the TOML file at the path and deserializes it.
+    pub fn read_toml_file<T>(&mut self, path: &str) -> io::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let bytes = self.entry_by_path(path)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} not in archive", path))
+        })?;
+        let source =
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        toml::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Verifies the SHA-256 checksums of the files listed in the sums file at `sums_path`,
+    /// returning an error for each file that doesn't match.
+    ///
+    /// The sums file can either be in the `sha256sum` format (`<hash>  <path>`) or the BSD format
+    /// (`SHA256 (<path>) = <hash>`), with paths relative to the directory of the sums file.
+    pub fn verify_entry_checksums(&mut self, sums_path: &str) -> io::Result<Vec<ChecksumError>> {
+        let sums = self.entry_by_path(sums_path)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not in archive", sums_path),
+            )
+        })?;
+        let sums =
+            String::from_utf8(sums).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let directory = Path::new(sums_path)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+
+        let mut errors = Vec::new();
+
+        for line in sums.lines().filter(|line| !line.trim().is_empty()) {
+            let (expected, file) = parse_checksum_line(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid checksum line \"{}\"", line),
+                )
+            })?;
+            let path = directory.join(file).to_string_lossy().into_owned();
+
+            let actual = self
+                .entry_by_path(&path)?
+                .map(|data| format!("{:x}", Sha256::digest(&data)));
+
+            if actual.as_deref() != Some(expected.to_lowercase().as_str()) {
+                errors.push(ChecksumError {
+                    path,
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        Ok(errors)
+    }
+}
+
+/// Sets the mode of the file from its tar header.
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+/// Modes only exist on unix, so files keep the default permissions elsewhere.
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
 }
 
-pub struct DenoArchiveLoader(Arc<Mutex<DenoArchiveInner>>);
+/// Checks that the SHA-256 digest of the data matches the expected hex digest, returning the
+/// actual digest if it doesn't.
+pub fn verify_sha256(data: &[u8], expected_hex: &str) -> Result<(), String> {
+    let actual = format!("{:x}", Sha256::digest(data));
+    if actual.eq_ignore_ascii_case(expected_hex.trim()) {
+        Ok(())
+    } else {
+        Err(actual)
+    }
+}
+
+impl Clone for DenoArchive {
+    fn clone(&self) -> Self {
+        Self {
+            module_name: self.module_name.clone(),
+            version: self.version.clone(),
+            buffer: self.buffer.clone(),
+            archive: Archive::new(Cursor::new(self.buffer.clone())),
+            index: self.index.clone(),
+        }
+    }
+}
+
+/// A symbolic link in a [DenoArchive].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkEntry {
+    pub path: String,
+    /// The path the link points to, which may be relative to the directory of the link.
+    pub target: String,
+}
+
+/// A rough estimate of how hard an archive is to parse, from
+/// [DenoArchive::estimate_parsed_complexity].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComplexityEstimate {
+    pub typescript_files: usize,
+    /// The average size of the TypeScript files in bytes.
+    pub avg_file_size: u64,
+    /// Whether any of the files are `.tsx` files.
+    pub has_tsx: bool,
+    /// Whether the start of any file has a line beginning with a decorator.
+    pub has_decorators: bool,
+}
+
+/// The lines of the TypeScript files in an archive, from [DenoArchive::count_lines_of_code].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinesOfCode {
+    pub total: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+/// Calculates the Shannon entropy of the bytes in bits per byte.
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / len;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Parses a line of a sums file into the expected hash and the path of the file.
+fn parse_checksum_line(line: &str) -> Option<(&str, &str)> {
+    if let Some(rest) = line.strip_prefix("SHA256 (") {
+        let (file, hash) = rest.split_at(rest.rfind(") = ")?);
+        return Some((hash[") = ".len()..].trim(), file));
+    }
+
+    let (hash, file) = line.split_at(line.find(char::is_whitespace)?);
+    // The `*` marks files that were hashed in binary mode.
+    let file = file.trim_start();
+    Some((hash, file.strip_prefix('*').unwrap_or(file)))
+}
+
+/// A file whose checksum doesn't match the one in a sums file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumError {
+    pub path: String,
+    pub expected: String,
+    /// The checksum of the file, or [None] if it isn't in the archive.
+    pub actual: Option<String>,
+}
+
+/// A function applied to the source of each file before it's parsed.
+type Transform = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+#[derive(Clone)]
+pub struct DenoArchiveLoader {
+    inner: Arc<Mutex<DenoArchiveInner>>,
+    transform: Option<Transform>,
+    npm: Option<Arc<NpmResolver>>,
+    /// Prefixes of specifiers that are replaced before resolving them.
+    aliases: Vec<(String, String)>,
+    /// The parser options used for every file, on top of which the extension's are enabled.
+    ts_config: TsConfig,
+}
 
 struct DenoArchiveInner {
     // A mutex is used because the loading is a asynchronous.
     archive: DenoArchive,
     // A cache for files inside of the deno archive so they don't need to be read to again.
     cache: HashMap<String, String>,
+    stats: LoaderStats,
+    // When the loader was created, which the start of each load event is relative to.
+    created: Instant,
+    events: Vec<LoadEvent>,
+}
+
+/// A file listed in a manifest used to warm the cache of a [DenoArchiveLoader].
+#[derive(Debug, Clone, Deserialize)]
+struct ManifestEntry {
+    specifier: String,
+    /// The SHA-256 hex digest of the source of the file.
+    sha256: String,
+}
+
+/// A call to load a file from a [DenoArchiveLoader] and how long it took.
+#[derive(Debug, Clone)]
+pub struct LoadEvent {
+    pub specifier: String,
+    /// When the load started, relative to when the loader was created.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Statistics about the files loaded by a [DenoArchiveLoader].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoaderStats {
+    /// How many times a file was requested from the loader.
+    pub total_loads: u64,
+    /// How many of the loads were served from the cache.
+    pub cache_hits: u64,
+    /// How many of the loads had to read the file from the archive.
+    pub cache_misses: u64,
+    /// The total size of the files read from the archive.
+    pub total_bytes_read: u64,
+}
+
+impl DenoArchiveLoader {
+    /// Creates a loader from a tar.gz file, reading it asynchronously.
+    pub async fn from_async_file(
+        module_name: String,
+        version: String,
+        mut file: File,
+    ) -> io::Result<Self> {
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).await?;
+
+        let archive = DenoArchive::from_reader(module_name, version, Cursor::new(buffer))?;
+        Ok(archive.into())
+    }
+
+    /// Creates a loader for the union of the archives, preferring the files of later archives
+    /// when several contain the same path.
+    ///
+    /// The merged archive keeps the module name and version of the first archive.
+    pub fn from_archives(archives: Vec<DenoArchive>) -> io::Result<Self> {
+        let mut archives = archives.into_iter();
+        let first = archives.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no archives to load from")
+        })?;
+        let module_name = first.module_name.clone();
+        let version = first.version.clone();
+
+        let mut archive = archives.try_fold(first, DenoArchive::merge)?;
+        archive.module_name = module_name;
+        archive.version = version;
+
+        Ok(archive.into())
+    }
+
+    /// Creates a loader for a copy of the same archive with an empty cache, so files are read
+    /// from the archive again when re-parsing.
+    pub async fn clone_with_empty_cache(&self) -> Self {
+        let inner = self.inner.lock().await;
+        let loader: Self = inner.archive.clone().into();
+
+        Self {
+            transform: self.transform.clone(),
+            npm: self.npm.clone(),
+            aliases: self.aliases.clone(),
+            ts_config: self.ts_config,
+            ..loader
+        }
+    }
+
+    /// Applies the callback to the source of every file before it's parsed, which can be used to
+    /// strip syntax that isn't supported by the parser.
+    pub fn with_transform<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.transform = Some(Arc::new(callback));
+        self
+    }
+
+    /// Replaces the `from` prefix of specifiers with `to` before resolving them, such as to map
+    /// `@shared/` to `/packages/shared/` in a monorepo.
+    ///
+    /// If several aliases match a specifier the one with the longest prefix is used.
+    pub fn with_alias(mut self, from: String, to: String) -> Self {
+        self.aliases.push((from, to));
+        self
+    }
+
+    /// Parses every file with the options, such as to enable decorators, along with the ones
+    /// needed by the extension of the file.
+    pub fn with_ts_config(mut self, ts_config: TsConfig) -> Self {
+        self.ts_config = ts_config;
+        self
+    }
+
+    /// Loads `npm:` specifiers from a CDN using the resolver, which are loaded as stubs otherwise.
+    pub fn with_npm_resolver(mut self, resolver: NpmResolver) -> Self {
+        self.npm = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Fills the cache with the files listed in a JSON manifest of `{ "specifier", "sha256" }`
+    /// entries, so builds parse the exact sources the manifest was made from.
+    ///
+    /// Files that are already cached are kept if their SHA-256 checksum matches and loaded from
+    /// the archive again otherwise. It's an error if the source in the archive doesn't match the
+    /// manifest either.
+    pub async fn warm_from_manifest(&self, manifest: &Path) -> io::Result<()> {
+        let manifest = tokio::fs::read(manifest).await?;
+        let entries: Vec<ManifestEntry> = serde_json::from_slice(&manifest)?;
+
+        let mut inner = self.inner.lock().await;
+        for entry in entries {
+            if let Some(source) = inner.cache.get(&entry.specifier) {
+                if verify_sha256(source.as_bytes(), &entry.sha256).is_ok() {
+                    continue;
+                }
+                tracing::debug!(
+                    "Cached source of {} doesn't match the manifest",
+                    entry.specifier
+                );
+            }
+
+            let source = inner
+                .read(&entry.specifier, self.transform.as_ref())
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+            if let Err(actual) = verify_sha256(source.as_bytes(), &entry.sha256) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "checksum of {} is {} but the manifest expects {}",
+                        entry.specifier, actual, entry.sha256
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the specifier is of a module that's loaded as a stub, which are Node's built-in
+    /// modules and npm packages when there's no resolver to load them with.
+    fn is_stub(&self, specifier: &str) -> bool {
+        specifier.starts_with("node:") || (specifier.starts_with("npm:") && self.npm.is_none())
+    }
+
+    /// Gets a copy of the cached sources of every file loaded so far, keyed by their specifier.
+    ///
+    /// The lock is only held while the cache is copied, so the snapshot may already be stale by
+    /// the time it's used if files are still being loaded.
+    pub async fn cached_specifiers_snapshot(&self) -> HashMap<String, String> {
+        self.inner.lock().await.cache.clone()
+    }
+
+    /// Gets every call made to load a file so far, in the order they were made.
+    pub async fn load_events(&self) -> Vec<LoadEvent> {
+        self.inner.lock().await.events.clone()
+    }
+
+    /// Gets statistics about the files that have been loaded so far.
+    pub async fn stats_summary(&self) -> LoaderStats {
+        self.inner.lock().await.stats
+    }
+}
+
+impl fmt::Debug for DenoArchiveLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The state can't be shown while it's being used to load a file.
+        match self.inner.try_lock() {
+            Ok(inner) => write!(
+                f,
+                "DenoArchiveLoader {{ cached_files: {}, archive: {}@{} }}",
+                inner.cache.len(),
+                inner.archive.module_name,
+                inner.archive.version
+            ),
+            Err(_) => write!(f, "DenoArchiveLoader {{ locked }}"),
+        }
+    }
 }
 
 impl From<DenoArchive> for DenoArchiveLoader {
     fn from(archive: DenoArchive) -> Self {
-        Self(Arc::new(Mutex::new(DenoArchiveInner {
-            archive,
-            cache: HashMap::default(),
-        })))
+        Self {
+            inner: Arc::new(Mutex::new(DenoArchiveInner {
+                archive,
+                cache: HashMap::default(),
+                stats: LoaderStats::default(),
+                created: Instant::now(),
+                events: Vec::new(),
+            })),
+            transform: None,
+            npm: None,
+            aliases: Vec::new(),
+            ts_config: TsConfig::default(),
+        }
     }
 }
 
 impl DocFileLoader for DenoArchiveLoader {
     fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, DocError> {
+        let aliased = self
+            .aliases
+            .iter()
+            .filter(|(from, _)| specifier.starts_with(from.as_str()))
+            .max_by_key(|(from, _)| from.len())
+            .map(|(from, to)| format!("{}{}", to, &specifier[from.len()..]));
+        let specifier = aliased.as_deref().unwrap_or(specifier);
+
         if specifier.starts_with("https://") {
             return Ok(specifier.to_string());
         }
 
-        log::debug!("Resolving {} referred to by {}", specifier, referrer);
-        todo!()
+        tracing::debug!("Resolving {} referred to by {}", specifier, referrer);
+
+        // Node's built-in modules are loaded as stubs.
+        if specifier.starts_with("node:") {
+            return Ok(specifier.to_string());
+        }
+
+        // Looking npm packages up in the registry is asynchronous, so it's done when they're
+        // loaded instead.
+        if specifier.starts_with("npm:") {
+            return Ok(specifier.to_string());
+        }
+
+        if referrer.starts_with("npm:") {
+            // Anything imported by a package that isn't relative is another package.
+            if !specifier.starts_with('.') {
+                return Ok(format!("npm:{}", specifier));
+            }
+
+            return self
+                .npm
+                .as_ref()
+                .and_then(|npm| npm.resolve_relative(specifier, referrer))
+                .ok_or_else(|| {
+                    DocError::Resolve(format!(
+                        "{} imported by {} couldn't be resolved",
+                        specifier, referrer
+                    ))
+                });
+        }
+
+        // Relative imports in remote modules are relative to the url they were loaded from.
+        if referrer.starts_with("https://") {
+            return Url::parse(referrer)
+                .and_then(|url| url.join(specifier))
+                .map(String::from)
+                .map_err(|e| DocError::Resolve(format!("{}: {}", specifier, e)));
+        }
+
+        resolve_archive_path(specifier, referrer)
     }
 
+    // The span is attached to the returned future so it covers the whole load.
+    #[tracing::instrument(skip(self))]
     fn load_source_code(
         &self,
         specifier: &str,
     ) -> LocalBoxFuture<Result<(Syntax, String), DocError>> {
-        log::debug!("Loading {} from deno archive", specifier);
+        if self.is_stub(specifier) {
+            tracing::debug!("Loading a stub for {}", specifier);
+            return Box::pin(async {
+                Ok((
+                    Syntax::Typescript(TsConfig::default()),
+                    STUB_SOURCE.to_string(),
+                ))
+            });
+        }
 
-        let this = self.0.clone();
-        let specifier = specifier.to_string();
-        Box::pin(async move {
-            let mut inner = this.lock().await;
-            let specifier_path = Path::new(&specifier);
-
-            let source = inner.cache.get(&specifier);
-            let had_source = source.is_some();
-            let source = match source {
-                Some(value) => value.clone(),
-                None => {
-                    let mut entry: DenoEntry<'_> = inner
-                        .archive
-                        .entries()
-                        .map_err(DocError::Io)?
-                        .filter_map(Result::ok)
-                        .find(|entry| {
-                            entry
-                                .path()
-                                .map(|x| x.as_ref() == specifier_path)
-                                .unwrap_or(false)
-                        })
-                        .ok_or(DocError::Resolve(format!("{} not in archive", &specifier)))?;
-
-                    let mut buffer = Vec::with_capacity(entry.size() as usize);
-                    entry.read_to_end(&mut buffer).unwrap();
-                    String::from_utf8(buffer).unwrap()
+        let syntax = syntax_for_path_with(specifier, self.ts_config);
+
+        if let Some(npm) = self.npm.clone().filter(|_| specifier.starts_with("npm:")) {
+            tracing::debug!("Loading {} from npm", specifier);
+
+            let specifier = specifier.to_string();
+            return Box::pin(
+                async move {
+                    let source = npm
+                        .load(&specifier)
+                        .await
+                        .map_err(|e| DocError::Resolve(format!("{}: {}", specifier, e)))?;
+                    Ok((syntax, source))
                 }
-            };
+                .instrument(Span::current()),
+            );
+        }
+
+        tracing::debug!("Loading {} from deno archive", specifier);
+
+        let this = self.inner.clone();
+        let transform = self.transform.clone();
+        let specifier = specifier.to_string();
+        Box::pin(
+            async move {
+                let mut inner = this.lock().await;
+                let start = Instant::now();
+                let result = inner.load(&specifier, transform.as_ref());
+
+                let event = LoadEvent {
+                    specifier,
+                    start: start.duration_since(inner.created),
+                    duration: start.elapsed(),
+                };
+                inner.events.push(event);
 
-            if !had_source {
-                inner.cache.insert(specifier, source.clone());
+                result.map(|source| (syntax, source))
             }
+            .instrument(Span::current()),
+        )
+    }
+}
 
-            Ok((Syntax::Typescript(TsConfig::default()), source))
-        })
+impl DenoArchiveInner {
+    /// Loads the source of the file at the specifier, from the cache if it's been loaded before.
+    fn load(&mut self, specifier: &str, transform: Option<&Transform>) -> Result<String, DocError> {
+        self.stats.total_loads += 1;
+
+        if let Some(source) = self.cache.get(specifier) {
+            self.stats.cache_hits += 1;
+            return Ok(source.clone());
+        }
+        self.stats.cache_misses += 1;
+
+        self.read(specifier, transform)
+    }
+
+    /// Reads the source of the file at the specifier from the archive and caches it.
+    fn read(&mut self, specifier: &str, transform: Option<&Transform>) -> Result<String, DocError> {
+        let source = read_entry(&mut self.archive, specifier)?;
+        self.stats.total_bytes_read += source.len() as u64;
+
+        let source = match transform {
+            Some(transform) => transform(source),
+            None => source,
+        };
+        self.cache.insert(specifier.to_string(), source.clone());
+
+        Ok(source)
+    }
+}
+
+/// Selects the syntax of the file from its extension, enabling JSX for `.tsx` files and ambient
+/// declarations for `.d.ts` files.
+pub fn syntax_for_path(path: &str) -> Syntax {
+    syntax_for_path_with(path, TsConfig::default())
+}
+
+/// Selects the syntax of the file like [syntax_for_path], keeping any options enabled in the base
+/// config.
+pub fn syntax_for_path_with(path: &str, base: TsConfig) -> Syntax {
+    Syntax::Typescript(TsConfig {
+        tsx: base.tsx || path.ends_with(".tsx"),
+        dts: base.dts || path.ends_with(".d.ts"),
+        ..base
+    })
+}
+
+/// The source loaded for modules that can't be read, which exports nothing.
+const STUB_SOURCE: &str = "export {};";
+
+/// Resolves a specifier relative to the file in the archive that imported it, producing the path
+/// of the file in the archive.
+///
+/// Specifiers starting with `/` are relative to the root directory of the archive rather than the
+/// referrer.
+fn resolve_archive_path(specifier: &str, referrer: &str) -> Result<String, DocError> {
+    let mut components: Vec<&str> = referrer.split('/').filter(|c| !c.is_empty()).collect();
+    // The last component of the referrer is the file itself.
+    components.pop();
+
+    if specifier.starts_with('/') {
+        components.truncate(1);
     }
+
+    for component in specifier.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                // The first component is the root directory every file in the archive is in.
+                if components.len() <= 1 {
+                    return Err(DocError::Resolve(format!(
+                        "{} imported by {} is outside of the archive",
+                        specifier, referrer
+                    )));
+                }
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    Ok(components.join("/"))
+}
+
+/// Reads the source of the file at the specifier out of the archive.
+fn read_entry(archive: &mut DenoArchive, specifier: &str) -> Result<String, DocError> {
+    let buffer = archive
+        .entry_by_path(specifier)
+        .map_err(DocError::Io)?
+        .ok_or_else(|| DocError::Resolve(format!("{} not in archive", specifier)))?;
+
+    String::from_utf8(buffer)
+        .map_err(|e| DocError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+/// A file read out of a [DenoArchive], owning its path and contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedDenoEntry {
+    pub path: String,
+    pub content: String,
 }
 
 /// A file in a [DenoArchive].
-pub struct DenoEntry<'archive>(Entry<'archive, Cursor<Vec<u8>>>);
+pub struct DenoEntry<'archive>(Entry<'archive, Cursor<Arc<[u8]>>>);
+
+impl<'archive> DenoEntry<'archive> {
+    /// Gets the file mode stored in the tar header of the entry.
+    pub fn permissions(&self) -> io::Result<u32> {
+        self.header().mode()
+    }
+
+    /// Checks if the entry is a `.ts` or `.tsx` file.
+    pub fn is_typescript(&self) -> bool {
+        self.path()
+            .map(|path| {
+                matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("ts") | Some("tsx")
+                )
+            })
+            .unwrap_or(false)
+    }
+}
 
 impl<'archive> Deref for DenoEntry<'archive> {
-    type Target = Entry<'archive, Cursor<Vec<u8>>>;
+    type Target = Entry<'archive, Cursor<Arc<[u8]>>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -158,3 +1327,263 @@ impl<'archive> DerefMut for DenoEntry<'archive> {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use deno_doc::DocParser;
+    use tar::EntryType;
+
+    use super::*;
+
+    /// The root directory every entry of a fixture is in, like the `owner-repo-ref/` directory of
+    /// GitHub tarballs.
+    const ROOT: &str = "example-1.0.0";
+
+    /// The SHA-256 digest of `hello`.
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    /// Builds a tar.gz archive laid out like the tarballs modules are downloaded as.
+    struct Fixture {
+        builder: Builder<GzEncoder<Vec<u8>>>,
+    }
+
+    impl Fixture {
+        /// Starts an archive with a global header and the root directory.
+        fn new() -> Self {
+            let mut fixture = Self {
+                builder: Builder::new(GzEncoder::new(Vec::new(), Compression::default())),
+            };
+
+            let mut header = Header::new_ustar();
+            header.set_entry_type(EntryType::XGlobalHeader);
+            header.set_size(0);
+            fixture
+                .builder
+                .append_data(&mut header, "pax_global_header", io::empty())
+                .unwrap();
+
+            fixture.entry("", EntryType::Directory, 0o755, "")
+        }
+
+        fn file(self, path: &str, contents: &str) -> Self {
+            self.entry(path, EntryType::Regular, 0o644, contents)
+        }
+
+        fn executable(self, path: &str, contents: &str) -> Self {
+            self.entry(path, EntryType::Regular, 0o755, contents)
+        }
+
+        fn directory(self, path: &str) -> Self {
+            self.entry(path, EntryType::Directory, 0o755, "")
+        }
+
+        fn symlink(mut self, path: &str, target: &str) -> Self {
+            let mut header = Header::new_ustar();
+            header.set_entry_type(EntryType::Symlink);
+            header.set_mode(0o777);
+            header.set_size(0);
+            header.set_link_name(target).unwrap();
+            self.builder
+                .append_data(&mut header, format!("{}/{}", ROOT, path), io::empty())
+                .unwrap();
+            self
+        }
+
+        fn entry(mut self, path: &str, entry_type: EntryType, mode: u32, contents: &str) -> Self {
+            let mut header = Header::new_ustar();
+            header.set_entry_type(entry_type);
+            header.set_mode(mode);
+            header.set_size(contents.len() as u64);
+            self.builder
+                .append_data(
+                    &mut header,
+                    format!("{}/{}", ROOT, path),
+                    contents.as_bytes(),
+                )
+                .unwrap();
+            self
+        }
+
+        fn build(self) -> DenoArchive {
+            let tarball = self.builder.into_inner().unwrap().finish().unwrap();
+            DenoArchive::from_reader(
+                "example".to_string(),
+                "1.0.0".to_string(),
+                Cursor::new(tarball),
+            )
+            .unwrap()
+        }
+    }
+
+    #[test]
+    fn root_directory_is_stable() {
+        let mut archive = Fixture::new().file("mod.ts", "export {};").build();
+
+        let first = archive.root_directory().unwrap();
+        let second = archive.root_directory().unwrap();
+        assert!(first.as_deref().unwrap().starts_with(ROOT));
+        assert_eq!(first, second);
+
+        // Reading the entries in between doesn't move where the root directory is read from.
+        assert_eq!(archive.list_typescript_files().unwrap().len(), 1);
+        assert_eq!(archive.root_directory().unwrap(), first);
+        assert_eq!(archive.clone().root_directory().unwrap(), first);
+    }
+
+    #[test]
+    fn list_typescript_files() {
+        let mut archive = Fixture::new()
+            .file("mod.ts", "export * from './src/app.tsx';")
+            .file("deno.json", "{}")
+            .directory("src")
+            .file("src/app.tsx", "export const app = <div />;")
+            .file("src/README.md", "# App")
+            // Directories are left out even if they're named like a TypeScript file.
+            .directory("types.ts")
+            .file("types.ts/index.d.ts", "export type Id = string;")
+            .build();
+
+        assert_eq!(
+            archive.list_typescript_files().unwrap(),
+            vec![
+                format!("{}/mod.ts", ROOT),
+                format!("{}/src/app.tsx", ROOT),
+                format!("{}/types.ts/index.d.ts", ROOT),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_to_dir() {
+        let mut archive = Fixture::new()
+            .file("mod.ts", "export * from './src/lib.ts';")
+            .directory("src")
+            .file("src/lib.ts", "export const answer = 42;")
+            .executable("cli.ts", "#!/usr/bin/env -S deno run")
+            .symlink("link.ts", "mod.ts")
+            .build();
+        let dest = tempfile::tempdir().unwrap();
+
+        archive.extract_to_dir(dest.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.path().join("mod.ts")).unwrap(),
+            "export * from './src/lib.ts';"
+        );
+        assert_eq!(
+            fs::read_to_string(dest.path().join("src/lib.ts")).unwrap(),
+            "export const answer = 42;"
+        );
+        assert!(!dest.path().join(ROOT).exists());
+        assert!(fs::symlink_metadata(dest.path().join("link.ts")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_to_dir_keeps_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut archive = Fixture::new()
+            .file("mod.ts", "export {};")
+            .executable("cli.ts", "#!/usr/bin/env -S deno run")
+            .build();
+        let dest = tempfile::tempdir().unwrap();
+
+        archive.extract_to_dir(dest.path()).unwrap();
+
+        let mode = |path: &str| {
+            fs::metadata(dest.path().join(path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode("mod.ts"), 0o644);
+        assert_eq!(mode("cli.ts"), 0o755);
+    }
+
+    #[test]
+    fn verify_sha256_accepts_matching_data() {
+        assert_eq!(verify_sha256(b"hello", HELLO_SHA256), Ok(()));
+        assert_eq!(
+            verify_sha256(b"hello", &HELLO_SHA256.to_uppercase()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn verify_sha256_rejects_mutated_data() {
+        let mut data = b"hello".to_vec();
+        data[0] ^= 1;
+
+        let actual = verify_sha256(&data, HELLO_SHA256).unwrap_err();
+        assert_ne!(actual, HELLO_SHA256);
+        assert_eq!(verify_sha256(&data, &actual), Ok(()));
+    }
+
+    #[test]
+    fn npm_and_node_specifiers_resolve_to_themselves() {
+        let loader = DenoArchiveLoader::from(Fixture::new().build());
+        let referrer = format!("{}/mod.ts", ROOT);
+
+        for specifier in ["npm:lodash@4", "npm:@std/path@1/posix", "node:fs"].iter() {
+            assert_eq!(loader.resolve(specifier, &referrer).unwrap(), *specifier);
+        }
+    }
+
+    #[tokio::test]
+    async fn npm_and_node_imports_are_stubbed() {
+        let archive = Fixture::new()
+            .file(
+                "mod.ts",
+                r#"
+import chalk from "npm:chalk@5";
+export * from "npm:lodash@4";
+export * from "node:path";
+
+export function run(): void {}
+"#,
+            )
+            .build();
+        let doc_parser = DocParser::new(Box::new(DenoArchiveLoader::from(archive)), false);
+
+        let nodes = doc_parser.parse(&format!("{}/mod.ts", ROOT)).await.unwrap();
+        assert!(nodes.iter().any(|node| node.name == "run"));
+    }
+
+    fn ts_config(syntax: Syntax) -> TsConfig {
+        match syntax {
+            Syntax::Typescript(ts_config) => ts_config,
+            syntax => panic!("expected TypeScript syntax, got {:?}", syntax),
+        }
+    }
+
+    #[test]
+    fn syntax_for_path_uses_the_extension() {
+        let ts = ts_config(syntax_for_path("example-1.0.0/mod.ts"));
+        assert!(!ts.tsx);
+        assert!(!ts.dts);
+
+        let tsx = ts_config(syntax_for_path("example-1.0.0/app.tsx"));
+        assert!(tsx.tsx);
+        assert!(!tsx.dts);
+
+        let dts = ts_config(syntax_for_path("example-1.0.0/types.d.ts"));
+        assert!(!dts.tsx);
+        assert!(dts.dts);
+    }
+
+    #[test]
+    fn syntax_for_path_with_keeps_the_base() {
+        let base = TsConfig {
+            decorators: true,
+            no_early_errors: true,
+            ..TsConfig::default()
+        };
+
+        let tsx = ts_config(syntax_for_path_with("example-1.0.0/app.tsx", base));
+        assert!(tsx.tsx);
+        assert!(tsx.decorators);
+        assert!(tsx.no_early_errors);
+    }
+}
@@ -0,0 +1,205 @@
+//! Compares the exported symbols of two versions of a module.
+
+use deno_doc::{DocNode, DocNodeKind};
+use serde_json::Value;
+
+use crate::output::changelog::Changes;
+
+/// The exported symbols that were added, removed or changed between two versions of a module.
+#[derive(Debug, Default)]
+pub struct DocDiff<'a> {
+    pub added: Vec<&'a DocNode>,
+    pub removed: Vec<&'a DocNode>,
+    /// The older and newer node of each symbol that changed.
+    pub changed: Vec<(&'a DocNode, &'a DocNode)>,
+}
+
+impl<'a> DocDiff<'a> {
+    /// Converts the diff into the changes written to a changelog, which only have the names of
+    /// the symbols, separating the symbols where only the JSDoc summary changed.
+    pub fn into_changes(self, module_name: &str, from_version: &str, to_version: &str) -> Changes {
+        let (changed, docs_changed): (Vec<_>, Vec<_>) = self
+            .changed
+            .iter()
+            .partition(|(older, newer)| has_changed_signature(older, newer));
+        let names = |nodes: Vec<&(&DocNode, &DocNode)>| -> Vec<String> {
+            nodes.iter().map(|(_, newer)| newer.name.clone()).collect()
+        };
+
+        Changes {
+            module_name: module_name.to_string(),
+            from_version: from_version.to_string(),
+            to_version: to_version.to_string(),
+            added: self.added.iter().map(|node| node.name.clone()).collect(),
+            removed: self.removed.iter().map(|node| node.name.clone()).collect(),
+            changed: names(changed),
+            docs_changed: names(docs_changed),
+        }
+    }
+}
+
+/// Compares the nodes of two versions, matching symbols by name.
+///
+/// A symbol has changed if its kind, the summary of its JSDoc or its signature is different.
+/// Only the first node of each name is compared, so overloads of a function are compared by their
+/// first declaration. The module doc and imports aren't symbols, so they're left out.
+pub fn diff_versions<'a>(older: &'a [DocNode], newer: &'a [DocNode]) -> DocDiff<'a> {
+    let older = first_symbol_of_each_name(older);
+    let newer = first_symbol_of_each_name(newer);
+    let mut diff = DocDiff::default();
+
+    for &node in &newer {
+        match older.iter().find(|older| older.name == node.name) {
+            Some(&older) if has_changed(older, node) => diff.changed.push((older, node)),
+            Some(_) => {}
+            None => diff.added.push(node),
+        }
+    }
+
+    for &node in &older {
+        if !newer.iter().any(|newer| newer.name == node.name) {
+            diff.removed.push(node);
+        }
+    }
+
+    diff
+}
+
+/// Gets the first node with each name that isn't the module doc or an import, in the order they
+/// were parsed.
+fn first_symbol_of_each_name(nodes: &[DocNode]) -> Vec<&DocNode> {
+    let mut first: Vec<&DocNode> = Vec::new();
+    for node in nodes {
+        let is_symbol = !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import);
+        if is_symbol && !first.iter().any(|existing| existing.name == node.name) {
+            first.push(node);
+        }
+    }
+    first
+}
+
+/// Checks if the kind, JSDoc summary or signature of a symbol differs between two versions.
+fn has_changed(older: &DocNode, newer: &DocNode) -> bool {
+    has_changed_signature(older, newer) || js_doc_summary(older) != js_doc_summary(newer)
+}
+
+/// Checks if the kind or signature of a symbol differs between two versions, which can break code
+/// using it unlike a change to its JSDoc.
+fn has_changed_signature(older: &DocNode, newer: &DocNode) -> bool {
+    older.kind != newer.kind || signature(older) != signature(newer)
+}
+
+/// Gets the first line of the JSDoc of the node that isn't empty.
+fn js_doc_summary(node: &DocNode) -> Option<&str> {
+    node.js_doc
+        .as_deref()?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+}
+
+/// Serializes the node without its location or JSDoc, which don't affect how it's used.
+fn signature(node: &DocNode) -> Option<Value> {
+    let mut value = serde_json::to_value(node).ok()?;
+    if let Value::Object(map) = &mut value {
+        map.remove("location");
+        map.remove("jsDoc");
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_source;
+
+    const OLDER: &str = r#"
+export function kept(): void {}
+
+/** Old summary. */
+export function documented(): void {}
+
+export function removed(): void {}
+
+export function retyped(value: string): void {}
+
+export const rekinded = 1;
+"#;
+
+    const NEWER: &str = r#"
+export function kept(): void {}
+
+/** New summary. */
+export function documented(): void {}
+
+export function retyped(value: number): void {}
+
+export function rekinded(): void {}
+
+export class Added {}
+"#;
+
+    fn names<'a>(nodes: impl IntoIterator<Item = &'a DocNode>) -> Vec<&'a str> {
+        let mut names: Vec<&str> = nodes.into_iter().map(|node| node.name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[tokio::test]
+    async fn diff_versions_finds_each_change() {
+        let older = parse_source(OLDER).await;
+        let newer = parse_source(NEWER).await;
+        let diff = diff_versions(&older, &newer);
+
+        assert_eq!(names(diff.added.iter().copied()), vec!["Added"]);
+        assert_eq!(names(diff.removed.iter().copied()), vec!["removed"]);
+        assert_eq!(
+            names(diff.changed.iter().map(|(_, newer)| *newer)),
+            vec!["documented", "rekinded", "retyped"]
+        );
+        assert!(diff
+            .changed
+            .iter()
+            .all(|(older, newer)| older.name == newer.name));
+    }
+
+    #[tokio::test]
+    async fn js_doc_changes_are_separate() {
+        let older = parse_source(OLDER).await;
+        let newer = parse_source(NEWER).await;
+        let mut changes = diff_versions(&older, &newer).into_changes("example", "1.0.0", "2.0.0");
+        changes.changed.sort_unstable();
+
+        assert_eq!(changes.changed, vec!["rekinded", "retyped"]);
+        assert_eq!(changes.docs_changed, vec!["documented"]);
+    }
+
+    #[tokio::test]
+    async fn module_doc_and_imports_are_skipped() {
+        let older = parse_source(OLDER).await;
+        let mut newer = older.clone();
+        for kind in [DocNodeKind::ModuleDoc, DocNodeKind::Import] {
+            let mut node = older[0].clone();
+            node.name = "dependency".to_string();
+            node.kind = kind;
+            newer.push(node);
+        }
+        let diff = diff_versions(&older, &newer);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn identical_versions_have_no_changes() {
+        let nodes = parse_source(OLDER).await;
+        let changes = diff_versions(&nodes, &nodes).into_changes("example", "1.0.0", "1.0.1");
+
+        assert_eq!(changes.module_name, "example");
+        assert!(changes.added.is_empty());
+        assert!(changes.removed.is_empty());
+        assert!(changes.changed.is_empty());
+        assert!(changes.docs_changed.is_empty());
+    }
+}
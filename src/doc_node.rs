@@ -0,0 +1,166 @@
+use deno_doc::{ts_type_param::TsTypeParamDef, DocNode, DocNodeKind};
+use serde::Serialize;
+
+/// A generic type parameter of a function or class.
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeParamDoc {
+    pub name: String,
+    /// The type the parameter has to be assignable to, from `T extends Constraint`.
+    pub constraint: Option<String>,
+    /// The type used when the parameter isn't provided, from `T = Default`.
+    pub default: Option<String>,
+}
+
+impl From<&TsTypeParamDef> for TypeParamDoc {
+    fn from(def: &TsTypeParamDef) -> Self {
+        Self {
+            name: def.name.clone(),
+            constraint: def.constraint.as_ref().map(|ts_type| ts_type.repr.clone()),
+            default: def.default.as_ref().map(|ts_type| ts_type.repr.clone()),
+        }
+    }
+}
+
+/// A reference from a `@see` tag, to either another symbol or a url.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeeRef {
+    pub is_url: bool,
+    /// The url or the name of the symbol that's referenced.
+    pub target: String,
+}
+
+impl SeeRef {
+    /// Parses the text following a `@see` tag, which is a url, a symbol name or a
+    /// `{@link target}` or `{@link target|text}` tag, optionally followed by a description.
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let target = match text.strip_prefix("{@link") {
+            Some(link) => link
+                .split(|c: char| c == '}' || c == '|')
+                .next()?
+                .split_whitespace()
+                .next()?,
+            None => text.split_whitespace().next()?,
+        };
+
+        Some(Self {
+            is_url: target.starts_with("https://") || target.starts_with("http://"),
+            target: target.to_string(),
+        })
+    }
+}
+
+/// Helpers for getting information out of [DocNode]s that `deno_doc` doesn't expose directly.
+pub trait DocNodeExt {
+    /// Finds the module-level doc comment among the nodes parsed from a module.
+    fn module_doc(nodes: &[DocNode]) -> Option<&str>;
+
+    /// Finds every node with the name, which for functions are each of their overloads.
+    fn overload_index<'a>(nodes: &'a [DocNode], name: &str) -> Vec<&'a DocNode>;
+
+    /// Finds the nodes that are async functions.
+    fn async_functions(nodes: &[DocNode]) -> Vec<&DocNode>;
+
+    /// Gets the generic type parameters of a function or class node.
+    fn type_params(&self) -> Vec<TypeParamDoc>;
+
+    /// Gets the names of the class a class node extends and the interfaces it implements, or the
+    /// interfaces an interface node extends.
+    fn inherits_from(&self) -> Vec<&str>;
+
+    /// Checks if the node is a class that implements the interface.
+    fn implements_interface(&self, name: &str) -> bool;
+
+    /// Checks if the JSDoc of the node has a `@deprecated` tag.
+    fn is_deprecated(&self) -> bool;
+
+    /// Gets the references of the `@see` tags in the JSDoc of the node.
+    fn see_also(&self) -> Vec<SeeRef>;
+}
+
+impl DocNodeExt for DocNode {
+    fn module_doc(nodes: &[DocNode]) -> Option<&str> {
+        nodes
+            .iter()
+            .find(|node| matches!(node.kind, DocNodeKind::ModuleDoc))
+            .and_then(|node| node.js_doc.as_deref())
+    }
+
+    fn overload_index<'a>(nodes: &'a [DocNode], name: &str) -> Vec<&'a DocNode> {
+        nodes.iter().filter(|node| node.name == name).collect()
+    }
+
+    fn async_functions(nodes: &[DocNode]) -> Vec<&DocNode> {
+        nodes
+            .iter()
+            .filter(|node| {
+                node.function_def
+                    .as_ref()
+                    .map(|function| function.is_async)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    fn type_params(&self) -> Vec<TypeParamDoc> {
+        let defs = if let Some(function) = &self.function_def {
+            &function.type_params
+        } else if let Some(class) = &self.class_def {
+            &class.type_params
+        } else {
+            return Vec::new();
+        };
+
+        defs.iter().map(TypeParamDoc::from).collect()
+    }
+
+    fn inherits_from(&self) -> Vec<&str> {
+        if let Some(class) = &self.class_def {
+            class
+                .extends
+                .as_deref()
+                .into_iter()
+                .chain(class.implements.iter().map(|ts_type| ts_type.repr.as_str()))
+                .collect()
+        } else if let Some(interface) = &self.interface_def {
+            interface
+                .extends
+                .iter()
+                .map(|ts_type| ts_type.repr.as_str())
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn implements_interface(&self, name: &str) -> bool {
+        self.class_def
+            .as_ref()
+            .map(|class| class.implements.iter().any(|ts_type| ts_type.repr == name))
+            .unwrap_or(false)
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.js_doc
+            .as_deref()
+            .map(|js_doc| {
+                js_doc
+                    .lines()
+                    .any(|line| line.trim_start().starts_with("@deprecated"))
+            })
+            .unwrap_or(false)
+    }
+
+    fn see_also(&self) -> Vec<SeeRef> {
+        self.js_doc
+            .as_deref()
+            .map(|js_doc| {
+                js_doc
+                    .lines()
+                    .filter_map(|line| line.trim_start().strip_prefix("@see"))
+                    .filter_map(SeeRef::parse)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
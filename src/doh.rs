@@ -0,0 +1,102 @@
+//! A DNS resolver that looks up hosts over HTTPS so lookups aren't leaked to the local network.
+
+use std::{
+    error::Error,
+    net::{IpAddr, SocketAddr},
+};
+
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::ACCEPT,
+    Client,
+};
+use serde::Deserialize;
+
+/// The DNS record type of IPv4 addresses.
+const RECORD_TYPE_A: u16 = 1;
+/// The DNS record type of IPv6 addresses.
+const RECORD_TYPE_AAAA: u16 = 28;
+
+/// Resolves hosts using the JSON API of a DNS-over-HTTPS endpoint, such as
+/// `https://cloudflare-dns.com/dns-query`.
+///
+/// The host of the endpoint itself is resolved by the system, since something has to be.
+#[derive(Debug, Clone)]
+pub struct DohResolver {
+    client: Client,
+    url: String,
+}
+
+impl DohResolver {
+    /// Creates a resolver that sends its queries to the endpoint using the client.
+    pub fn new(client: Client, url: String) -> Self {
+        Self { client, url }
+    }
+
+    /// Looks up the addresses of the host for the record type.
+    async fn query(
+        &self,
+        host: &str,
+        record_type: u16,
+    ) -> Result<Vec<IpAddr>, Box<dyn Error + Send + Sync>> {
+        let response: DohResponse = self
+            .client
+            .get(&self.url)
+            .query(&[("name", host), ("type", &record_type.to_string())])
+            .header(ACCEPT, "application/dns-json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        // Answers can also include the CNAME records that were followed, which aren't addresses.
+        let addresses = response
+            .answer
+            .into_iter()
+            .filter(|answer| answer.record_type == record_type)
+            .filter_map(|answer| answer.data.parse().ok())
+            .collect();
+
+        Ok(addresses)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+
+        Box::pin(async move {
+            let host = name.as_str();
+            tracing::debug!("Resolving {} over HTTPS.", host);
+
+            let mut addresses = resolver.query(host, RECORD_TYPE_A).await?;
+            addresses.extend(resolver.query(host, RECORD_TYPE_AAAA).await?);
+
+            if addresses.is_empty() {
+                return Err(format!("no addresses found for {}", host).into());
+            }
+
+            // The port is replaced by the one of the url being requested.
+            let addrs: Addrs = Box::new(
+                addresses
+                    .into_iter()
+                    .map(|address| SocketAddr::new(address, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
@@ -0,0 +1,75 @@
+use std::{collections::BTreeMap, io, path::Path};
+
+use deno_doc_info_generator::deno_archive::DenoArchive;
+
+/// Creates a Deno export map for the public TypeScript files in the archive, suitable for the
+/// `exports` field of a `deno.json`.
+///
+/// A `mod.ts` is exported as the directory it's in, and every other file is exported as its
+/// path without the extension. Tests, declaration files, and anything in a directory or file
+/// starting with `_` or `.` are treated as private.
+pub fn export_map(
+    archive: &mut DenoArchive,
+    root_directory: &str,
+) -> io::Result<BTreeMap<String, String>> {
+    let mut paths = Vec::new();
+    for entry in archive.iter_typescript_files()? {
+        if let Some(path) = entry?.path()?.to_str() {
+            paths.push(path.to_string());
+        }
+    }
+
+    let root = Path::new(root_directory);
+    let mut exports = BTreeMap::new();
+
+    for path in &paths {
+        let relative = match Path::new(path).strip_prefix(root) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+
+        if !is_public(relative) {
+            continue;
+        }
+
+        let target = format!("./{}", relative.to_string_lossy());
+        let specifier = match (relative.parent(), relative.file_stem()) {
+            (Some(parent), Some(stem)) if stem == "mod" => {
+                if parent.as_os_str().is_empty() {
+                    ".".to_string()
+                } else {
+                    format!("./{}", parent.to_string_lossy())
+                }
+            }
+            (Some(parent), Some(stem)) => {
+                format!("./{}", parent.join(stem).to_string_lossy())
+            }
+            _ => continue,
+        };
+
+        exports.insert(specifier, target);
+    }
+
+    Ok(exports)
+}
+
+/// Checks if the file at the path, relative to the root of the archive, should be exported.
+fn is_public(path: &Path) -> bool {
+    let file_name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(file_name) => file_name,
+        None => return false,
+    };
+
+    let is_test = file_name == "test.ts"
+        || file_name.ends_with("_test.ts")
+        || file_name.ends_with(".test.ts")
+        || file_name.ends_with("_test.tsx")
+        || file_name.ends_with(".test.tsx");
+
+    let is_hidden = path.iter().any(|component| {
+        let component = component.to_string_lossy();
+        component.starts_with('_') || component.starts_with('.') || component == "testdata"
+    });
+
+    !is_test && !is_hidden && !file_name.ends_with(".d.ts")
+}
@@ -1,6 +1,436 @@
-use reqwest::Client;
+use std::{
+    env,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use reqwest::{
+    header::{
+        HeaderMap, AUTHORIZATION, CONTENT_TYPE, IF_MODIFIED_SINCE, LAST_MODIFIED, RETRY_AFTER,
+    },
+    multipart::{Form, Part},
+    redirect::Policy,
+    Certificate, Client, ClientBuilder, RequestBuilder, Response, StatusCode,
+};
 use serde::Deserialize;
+use serde_json::{json, Value};
 use thiserror::Error;
+use tokio::{fs, io::AsyncWriteExt};
+
+use crate::doh::DohResolver;
+
+/// The v2 API with the metadata of every module in the registry, like its tags.
+const MODULES_API_URL: &str = "https://apiland.deno.dev/v2/modules";
+
+/// The user agent sent with every request made by a [DenoModuleClient].
+const USER_AGENT: &str = "deno-doc-info-generator";
+
+/// The largest metadata response that's read if a limit isn't configured, which is 10 MB.
+pub(crate) const DEFAULT_MAX_RESPONSE_SIZE: usize = 10 * 1024 * 1024;
+
+/// The upload type of modules published from a GitHub repository.
+const GITHUB_UPLOAD_TYPE: &str = "github";
+
+/// The upload type of modules published from a GitLab repository.
+const GITLAB_UPLOAD_TYPE: &str = "gitlab";
+
+/// The upload type of modules published from a tarball hosted at a url.
+const URL_UPLOAD_TYPE: &str = "url";
+
+/// A client for fetching modules and their metadata from the Deno registry.
+#[derive(Debug, Clone)]
+pub struct DenoModuleClient {
+    client: Client,
+    cache_dir: Option<PathBuf>,
+    registry_url: Option<String>,
+    /// The largest metadata response in bytes that will be read.
+    max_response_size: usize,
+    /// How metadata requests that fail with a transient error are retried.
+    retry_policy: RetryPolicy,
+    /// Whether requests and responses are logged, set by
+    /// [DenoModuleClientBuilder::debug_log_requests].
+    log_requests: bool,
+}
+
+impl DenoModuleClient {
+    /// Creates a builder for configuring a [DenoModuleClient].
+    pub fn builder() -> DenoModuleClientBuilder {
+        DenoModuleClientBuilder::default()
+    }
+
+    /// Gets the underlying HTTP client.
+    pub fn http(&self) -> &Client {
+        &self.client
+    }
+
+    /// Fetches metadata about the versions for the provided module.
+    pub async fn fetch_versions_for_module(
+        &self,
+        module_name: &str,
+    ) -> Result<DenoVersionsResponse, FetchError> {
+        fetch_with_retry(&self.retry_policy, || {
+            fetch_versions_for_module(self, module_name)
+        })
+        .await
+    }
+
+    /// Fetches the metadata about the specified version for a module.
+    pub async fn fetch_version_metadata(
+        &self,
+        module_name: &str,
+        version: &str,
+    ) -> Result<VersionMetadataResponse, FetchError> {
+        fetch_with_retry(&self.retry_policy, || async move {
+            match &self.cache_dir {
+                Some(cache_dir) => {
+                    fetch_version_metadata_cached(
+                        self,
+                        cache_dir,
+                        module_name,
+                        version,
+                        self.max_response_size,
+                    )
+                    .await
+                }
+                None => {
+                    fetch_version_metadata(self, module_name, version, self.max_response_size).await
+                }
+            }
+        })
+        .await
+    }
+
+    /// Fetches the metadata of each of the versions, at most `concurrency` at once, leaving out the
+    /// versions it couldn't be fetched for.
+    ///
+    /// The metadata is in the same order as the versions.
+    pub async fn fetch_all_version_metadata(
+        &self,
+        module_name: &str,
+        versions: &[String],
+        concurrency: usize,
+    ) -> Vec<(String, VersionMetadataResponse)> {
+        let results: Vec<_> = stream::iter(versions)
+            .map(|version| self.fetch_version_metadata(module_name, version))
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        collect_version_metadata(module_name, versions, results)
+    }
+
+    /// Lists the versions of the module published after `since`, newest first.
+    ///
+    /// Versions without an upload time in their metadata are left out.
+    pub async fn list_module_versions_since_date(
+        &self,
+        module_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<VersionInfo>, FetchError> {
+        let versions = self.fetch_versions_for_module(module_name).await?;
+        let mut newer_versions = Vec::new();
+
+        // The versions are listed newest first, so the rest are older once one is too old.
+        for version in versions.versions {
+            let metadata = self.fetch_version_metadata(module_name, &version).await?;
+            let uploaded_at = match metadata.uploaded_at {
+                Some(uploaded_at) => uploaded_at,
+                None => {
+                    tracing::debug!("Version {} of {} has no upload time.", version, module_name);
+                    continue;
+                }
+            };
+
+            if uploaded_at <= since {
+                break;
+            }
+
+            newer_versions.push(VersionInfo {
+                version,
+                uploaded_at,
+            });
+        }
+
+        Ok(newer_versions)
+    }
+
+    /// Fetches the tags of the module, like `cli` or `database`, from the v2 API.
+    pub async fn fetch_module_tags(&self, module_name: &str) -> Result<Vec<String>, FetchError> {
+        fetch_with_retry(&self.retry_policy, || fetch_module_tags(self, module_name)).await
+    }
+
+    /// Checks if the version of the module exists without downloading its metadata.
+    pub async fn version_exists(
+        &self,
+        module_name: &str,
+        version: &str,
+    ) -> Result<bool, FetchError> {
+        version_exists(self, module_name, version).await
+    }
+
+    /// Downloads the tarball at the url.
+    pub async fn fetch_tarball(&self, url: &str) -> Result<Bytes, FetchError> {
+        fetch_tarball(self, url).await
+    }
+
+    /// Downloads the tarball at the url straight to a file at `dest`, returning how many bytes
+    /// were written, and aborting if it's larger than `limit` bytes.
+    pub async fn download_tarball_to_file(
+        &self,
+        url: &str,
+        dest: &Path,
+        limit: u64,
+    ) -> Result<u64, FetchError> {
+        download_tarball_to_file(self, url, dest, limit).await
+    }
+
+    /// Downloads the tarball of every version of the module to
+    /// `{dest_dir}/{module}/{version}.tar.gz`, with at most `concurrency` versions downloading at
+    /// once.
+    ///
+    /// Versions that have already been downloaded are skipped. The results are in the same order
+    /// as the versions listed for the module.
+    pub async fn download_all_versions(
+        &self,
+        module_name: &str,
+        dest_dir: &Path,
+        concurrency: usize,
+    ) -> Vec<Result<PathBuf, FetchError>> {
+        let versions = match self.fetch_versions_for_module(module_name).await {
+            Ok(versions) => versions,
+            Err(e) => return vec![Err(e)],
+        };
+
+        let directory = dest_dir.join(module_name);
+        if let Err(e) = fs::create_dir_all(&directory).await {
+            return vec![Err(e.into())];
+        }
+
+        stream::iter(versions.versions)
+            .map(|version| {
+                let path = directory.join(format!("{}.tar.gz", version));
+                async move { self.download_version(module_name, &version, path).await }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Downloads the tarball of the version to the path, unless it already exists.
+    ///
+    /// The tarball is written to a temporary file that's then renamed, so an interrupted download
+    /// isn't skipped as already downloaded the next time.
+    async fn download_version(
+        &self,
+        module_name: &str,
+        version: &str,
+        path: PathBuf,
+    ) -> Result<PathBuf, FetchError> {
+        if fs::metadata(&path).await.is_ok() {
+            tracing::debug!(
+                "Skipping {}, it has already been downloaded.",
+                path.display()
+            );
+            return Ok(path);
+        }
+
+        let metadata = self.fetch_version_metadata(module_name, version).await?;
+        let url = metadata.upload_options.tarball_url()?;
+
+        let bytes = self.fetch_tarball(&url).await?;
+        let tmp_path = path.with_extension("gz.tmp");
+        fs::write(&tmp_path, &bytes).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        Ok(path)
+    }
+
+    /// Downloads the tarball at the url, aborting if it's larger than `limit` bytes.
+    pub async fn fetch_tarball_with_limit(
+        &self,
+        url: &str,
+        limit: u64,
+    ) -> Result<Bytes, FetchError> {
+        fetch_tarball_with_limit(self, url, limit).await
+    }
+
+    /// Uploads the tarball as a version of the module to the configured self-hosted registry,
+    /// authenticating with the bearer token.
+    pub async fn upload_module(
+        &self,
+        module_name: &str,
+        version: &str,
+        tarball: &Path,
+        token: &str,
+    ) -> Result<(), FetchError> {
+        let registry_url = self.registry_url.as_deref().ok_or(FetchError::NoRegistry)?;
+
+        tracing::debug!(
+            "Uploading {} as {}@{} to {}.",
+            tarball.display(),
+            module_name,
+            version,
+            registry_url
+        );
+        let bytes = fs::read(tarball).await?;
+        let file_name = tarball
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("{}.tar.gz", version));
+        let form = Form::new()
+            .text("name", module_name.to_string())
+            .text("version", version.to_string())
+            .part(
+                "tarball",
+                Part::bytes(bytes)
+                    .file_name(file_name)
+                    .mime_str("application/gzip")?,
+            );
+
+        let request = self
+            .client
+            .post(registry_url)
+            .bearer_auth(token)
+            .multipart(form);
+        send(self, request).await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// A builder for a [DenoModuleClient], delegating to a [ClientBuilder].
+pub struct DenoModuleClientBuilder {
+    inner: ClientBuilder,
+    cache_dir: Option<PathBuf>,
+    registry_url: Option<String>,
+    doh_url: Option<String>,
+    max_response_size: usize,
+    retry_policy: RetryPolicy,
+    debug_log_requests: bool,
+}
+
+impl Default for DenoModuleClientBuilder {
+    fn default() -> Self {
+        Self {
+            inner: ClientBuilder::new()
+                .redirect(Policy::default())
+                .user_agent(USER_AGENT),
+            cache_dir: None,
+            registry_url: None,
+            doh_url: None,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            retry_policy: RetryPolicy::default(),
+            debug_log_requests: false,
+        }
+    }
+}
+
+impl DenoModuleClientBuilder {
+    /// Controls whether invalid TLS certificates are accepted, which is useful for self-hosted
+    /// registries using self-signed certificates.
+    ///
+    /// This is only available in debug builds so certificate verification can't be disabled in
+    /// a release.
+    #[cfg(debug_assertions)]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.inner = self.inner.danger_accept_invalid_certs(accept_invalid_certs);
+        self
+    }
+
+    /// Adds a trusted root certificate, such as the one of a self-hosted registry.
+    pub fn add_root_certificate(mut self, cert: Certificate) -> Self {
+        self.inner = self.inner.add_root_certificate(cert);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept in the pool for each host.
+    pub fn max_connections_per_host(mut self, max: usize) -> Self {
+        self.inner = self.inner.pool_max_idle_per_host(max);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connection_timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.connect_timeout(timeout);
+        self
+    }
+
+    /// Sets the interval of TCP keep-alive probes on connections.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.inner = self.inner.tcp_keepalive(interval);
+        self
+    }
+
+    /// Sets the directory responses are cached in so they don't need to be downloaded again.
+    pub fn cache_dir<P>(mut self, cache_dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Sets the URL of the self-hosted registry modules are uploaded to.
+    pub fn registry_url<S>(mut self, registry_url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.registry_url = Some(registry_url.into());
+        self
+    }
+
+    /// Sets the largest metadata response in bytes that will be read, which is 10 MB by default.
+    pub fn max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Sets how metadata requests that fail with a transient error are retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Resolves hosts using the DNS-over-HTTPS endpoint, such as
+    /// `https://cloudflare-dns.com/dns-query`, instead of the system's resolver.
+    pub fn dns_over_https(mut self, doh_url: &str) -> Self {
+        self.doh_url = Some(doh_url.to_string());
+        self
+    }
+
+    /// Logs the method, url and headers of every request and the status and headers of every
+    /// response at the trace level, without their bodies.
+    pub fn debug_log_requests(mut self, enabled: bool) -> Self {
+        self.debug_log_requests = enabled;
+        self
+    }
+
+    /// Builds the [DenoModuleClient].
+    pub fn build(self) -> Result<DenoModuleClient, FetchError> {
+        let mut inner = self.inner;
+
+        if let Some(doh_url) = self.doh_url {
+            let doh_client = ClientBuilder::new().user_agent(USER_AGENT).build()?;
+            inner = inner.dns_resolver(Arc::new(DohResolver::new(doh_client, doh_url)));
+        }
+
+        Ok(DenoModuleClient {
+            client: inner.build()?,
+            cache_dir: self.cache_dir,
+            registry_url: self.registry_url,
+            max_response_size: self.max_response_size,
+            retry_policy: self.retry_policy,
+            log_requests: self.debug_log_requests,
+        })
+    }
+}
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct DenoVersionsResponse {
@@ -10,9 +440,70 @@ pub struct DenoVersionsResponse {
     pub versions: Vec<String>,
 }
 
+impl DenoVersionsResponse {
+    /// Gets the versions published after the version, oldest first, or nothing if the version
+    /// isn't in the list.
+    pub fn versions_since(&self, version: &str) -> Vec<&str> {
+        // The versions are listed newest first, so the newer versions come before it.
+        match self.versions.iter().position(|v| v == version) {
+            Some(index) => self.versions[..index]
+                .iter()
+                .rev()
+                .map(String::as_str)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct VersionMetadataResponse {
     pub upload_options: UploadOptions,
+    /// When the version was published.
+    #[serde(default)]
+    pub uploaded_at: Option<DateTime<Utc>>,
+    /// The SHA-256 hex digest of the tarball, which only some responses include.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+/// The metadata of a module returned by the v2 API, of which only the tags are used.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModuleResponse {
+    #[serde(default)]
+    pub tags: Vec<ModuleTag>,
+}
+
+/// A tag describing what a module is for, like `cli`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModuleTag {
+    /// What the tag was derived from, like `popularity` or `category`.
+    pub kind: String,
+    pub value: String,
+}
+
+/// A published version of a module and when it was published.
+#[derive(Debug, Clone)]
+pub struct VersionInfo {
+    pub version: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+impl VersionMetadataResponse {
+    /// Checks if the version was uploaded from a GitHub repository.
+    pub fn is_github_hosted(&self) -> bool {
+        self.upload_options.upload_options_type == GITHUB_UPLOAD_TYPE
+    }
+
+    /// Checks if the version was uploaded from a GitLab repository.
+    pub fn is_gitlab_hosted(&self) -> bool {
+        self.upload_options.upload_options_type == GITLAB_UPLOAD_TYPE
+    }
+
+    /// Checks if the version was uploaded as a tarball at a url.
+    pub fn is_url_hosted(&self) -> bool {
+        self.upload_options.upload_options_type == URL_UPLOAD_TYPE
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -26,30 +517,264 @@ pub struct UploadOptions {
 
 impl UploadOptions {
     /// Creates a link to where the library can be downloaded as a tarball.
-    pub fn tarball_url(&self) -> Option<String> {
+    ///
+    /// The meaning of the repository depends on the upload type: it's the `owner/name` of a
+    /// GitHub or GitLab repository, or the url of the tarball itself for `url` uploads.
+    pub fn tarball_url(&self) -> Result<String, FetchError> {
         match self.upload_options_type.as_str() {
-            "github" => Some(format!(
+            GITHUB_UPLOAD_TYPE => Ok(format!(
                 "https://api.github.com/repos/{}/tarball/{}",
                 self.repository, self.upload_options_ref
             )),
-            _ => None,
+            GITLAB_UPLOAD_TYPE => {
+                let name = self
+                    .repository
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(&self.repository);
+                Ok(format!(
+                    "https://gitlab.com/{}/-/archive/{2}/{}-{2}.tar.gz",
+                    self.repository, name, self.upload_options_ref
+                ))
+            }
+            URL_UPLOAD_TYPE => Ok(self.repository.clone()),
+            upload_type => Err(FetchError::UnsupportedUploadType(upload_type.to_string())),
+        }
+    }
+}
+
+/// How requests that fail with a transient error are retried, waiting longer after each attempt.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The most times a request is made, including the first attempt.
+    pub max_attempts: u8,
+    /// How long to wait before the first retry.
+    pub initial_delay: Duration,
+    /// How much the delay is multiplied by after each retry.
+    pub backoff_factor: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            backoff_factor: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default policy, overridden by the `DENO_DOC_MAX_RETRIES` and
+    /// `DENO_DOC_RETRY_DELAY_MS` environment variables when they're set.
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+
+        if let Some(retries) = env_var::<u8>("DENO_DOC_MAX_RETRIES") {
+            policy.max_attempts = retries.saturating_add(1);
+        }
+
+        if let Some(delay) = env_var::<u64>("DENO_DOC_RETRY_DELAY_MS") {
+            policy.initial_delay = Duration::from_millis(delay);
         }
+
+        policy
+    }
+}
+
+/// Parses the environment variable, logging a warning if it's set but invalid.
+fn env_var<T: FromStr>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+    match value.trim().parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid value \"{}\" for {}.", value, name);
+            None
+        }
+    }
+}
+
+/// Runs the request until it succeeds or fails with an error that isn't transient, retrying
+/// according to the policy.
+///
+/// If every attempt fails with a transient error the last one is wrapped in
+/// [FetchError::ExhaustedRetries].
+pub async fn fetch_with_retry<F, Fut, T>(
+    policy: &RetryPolicy,
+    mut fetch: F,
+) -> Result<T, FetchError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, FetchError>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut delay = policy.initial_delay;
+    let mut attempt = 1;
+
+    loop {
+        let error = match fetch().await {
+            Ok(value) => return Ok(value),
+            Err(e) if !e.is_transient() => return Err(e),
+            Err(e) => e,
+        };
+
+        if attempt >= max_attempts {
+            if attempt == 1 {
+                return Err(error);
+            }
+
+            return Err(FetchError::ExhaustedRetries {
+                attempts: attempt,
+                last_error: Box::new(error),
+            });
+        }
+
+        tracing::debug!(
+            "Attempt {} of {} failed, retrying in {:?}: {}",
+            attempt,
+            max_attempts,
+            delay,
+            error
+        );
+        // Wait at least as long as the server asked when rate limited.
+        let wait = match error.retry_after() {
+            Some(retry_after) => retry_after.max(delay),
+            None => delay,
+        };
+        tokio::time::sleep(wait).await;
+        delay = delay.mul_f64(policy.backoff_factor);
+        attempt += 1;
+    }
+}
+
+/// Something requests can be sent with, which is either a plain [Client] or a
+/// [DenoModuleClient] that can log them.
+///
+/// reqwest doesn't support middleware, so the functions in this module take one of these to
+/// know whether [send] should log the requests they make.
+pub trait RequestSender {
+    /// Gets the HTTP client requests are sent with.
+    fn http(&self) -> &Client;
+
+    /// Checks if requests and responses should be logged.
+    fn log_requests(&self) -> bool;
+}
+
+impl RequestSender for Client {
+    fn http(&self) -> &Client {
+        self
+    }
+
+    fn log_requests(&self) -> bool {
+        false
     }
 }
 
+impl RequestSender for DenoModuleClient {
+    fn http(&self) -> &Client {
+        &self.client
+    }
+
+    fn log_requests(&self) -> bool {
+        self.log_requests
+    }
+}
+
+/// Sends the request, logging the request and response at the trace level if the sender was
+/// configured to with [DenoModuleClientBuilder::debug_log_requests].
+///
+/// Bodies are never logged since they can be large and sensitive, and neither is the value of
+/// the `Authorization` header.
+pub(crate) async fn send(
+    sender: &impl RequestSender,
+    request: RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    if !sender.log_requests() {
+        return request.send().await;
+    }
+
+    let request = request.build()?;
+    tracing::trace!("--> {} {}", request.method(), request.url());
+    log_headers(request.headers());
+
+    let url = request.url().clone();
+    let response = sender.http().execute(request).await?;
+    tracing::trace!("<-- {} {}", response.status(), url);
+    log_headers(response.headers());
+
+    Ok(response)
+}
+
+/// Logs each of the headers at the trace level, hiding credentials.
+fn log_headers(headers: &HeaderMap) {
+    for (name, value) in headers {
+        if name == AUTHORIZATION {
+            tracing::trace!("    {}: <redacted>", name);
+        } else {
+            tracing::trace!("    {}: {:?}", name, value);
+        }
+    }
+}
+
+/// Turns responses with a status worth retrying, 429 or a server error, into an error, since
+/// Deno would otherwise have them reported as missing metadata.
+fn check_retryable_status(response: Response) -> Result<Response, FetchError> {
+    let response = check_rate_limit(response)?;
+
+    let status = response.status();
+    if status.is_server_error() {
+        return Err(FetchError::UnexpectedStatus(status));
+    }
+
+    Ok(response)
+}
+
+/// Turns 429 responses into [FetchError::RateLimited], with how long the `Retry-After` header
+/// says to wait.
+fn check_rate_limit(response: Response) -> Result<Response, FetchError> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(response);
+    }
+
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after);
+
+    Err(FetchError::RateLimited { retry_after })
+}
+
+/// Parses a `Retry-After` header, which is either a number of seconds or an HTTP-date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // A date in the past means the request can be retried straight away.
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
 /// Fetches metadata about the versions for the provided module.
+#[tracing::instrument(skip(client))]
 pub async fn fetch_versions_for_module(
-    client: &Client,
+    client: &impl RequestSender,
     module_name: &str,
 ) -> Result<DenoVersionsResponse, FetchError> {
-    log::debug!("Fetching versions for module {}.", module_name);
-    let response = client
-        .get(&format!(
-            "https://cdn.deno.land/{}/meta/versions.json",
-            module_name
-        ))
-        .send()
-        .await?;
+    tracing::debug!("Fetching versions for module {}.", module_name);
+    let request = client.http().get(&format!(
+        "https://cdn.deno.land/{}/meta/versions.json",
+        module_name
+    ));
+    let response = send(client, request).await?;
+    let response = check_retryable_status(response)?;
 
     // Deno returns a non-json content type if the module doesn't exist.
     match response.headers().get("Content-Type").map(|v| v.to_str()) {
@@ -58,32 +783,470 @@ pub async fn fetch_versions_for_module(
     }
 }
 
-/// Fetches the metadata about the specified version for a module.
+/// Fetches the tags of the module from the v2 API.
+#[tracing::instrument(skip(client))]
+pub async fn fetch_module_tags(
+    client: &impl RequestSender,
+    module_name: &str,
+) -> Result<Vec<String>, FetchError> {
+    tracing::debug!("Fetching tags for module {}.", module_name);
+    let request = client
+        .http()
+        .get(&format!("{}/{}", MODULES_API_URL, module_name));
+    let response = check_retryable_status(send(client, request).await?)?;
+
+    match response.status() {
+        StatusCode::NOT_FOUND => Err(FetchError::MetadataNotPresent),
+        status if !status.is_success() => Err(FetchError::UnexpectedStatus(status)),
+        _ => {
+            let module: ModuleResponse = response.json().await?;
+            Ok(module.tags.into_iter().map(|tag| tag.value).collect())
+        }
+    }
+}
+
+/// Fetches the metadata about the specified version for a module, erroring if the response is
+/// larger than `max_response_size` bytes.
+#[tracing::instrument(skip(client))]
 pub async fn fetch_version_metadata(
-    client: &Client,
+    client: &impl RequestSender,
     module_name: &str,
     version: &str,
+    max_response_size: usize,
 ) -> Result<VersionMetadataResponse, FetchError> {
-    log::debug!("Fetching version {} for module {}.", version, module_name);
-    let response = client
-        .get(&format!(
-            "https://cdn.deno.land/{}/versions/{}/meta/meta.json",
-            module_name, version
-        ))
-        .send()
-        .await?;
+    tracing::debug!("Fetching version {} for module {}.", version, module_name);
+    let request = client
+        .http()
+        .get(&version_metadata_url(module_name, version));
+    let response = send(client, request).await?;
+    let response = check_retryable_status(response)?;
 
     // Deno returns a non-json content type if the module doesn't exist.
     match response.headers().get("Content-Type").map(|v| v.to_str()) {
-        Some(Ok("application/json")) => response.json().await.map_err(FetchError::from),
+        Some(Ok("application/json")) => {
+            let body = read_body_with_limit(response, max_response_size as u64).await?;
+            Ok(serde_json::from_slice(&body)?)
+        }
         _ => Err(FetchError::MetadataNotPresent),
     }
 }
 
+/// Pairs each version with its metadata, logging the versions it couldn't be fetched for.
+fn collect_version_metadata(
+    module_name: &str,
+    versions: &[String],
+    results: Vec<Result<VersionMetadataResponse, FetchError>>,
+) -> Vec<(String, VersionMetadataResponse)> {
+    versions
+        .iter()
+        .zip(results)
+        .filter_map(|(version, result)| match result {
+            Ok(metadata) => Some((version.clone(), metadata)),
+            Err(e) => {
+                tracing::warn!(
+                    "Unable to fetch metadata for {}@{}: {}",
+                    module_name,
+                    version,
+                    e
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Fetches the metadata about the specified version for a module, storing it in the cache
+/// directory along with its `Last-Modified` header.
+///
+/// If the metadata has already been cached, it's only downloaded again if the server says it
+/// has been modified since. Responses larger than `max_response_size` bytes are an error.
+pub async fn fetch_version_metadata_cached(
+    client: &impl RequestSender,
+    cache_dir: &Path,
+    module_name: &str,
+    version: &str,
+    max_response_size: usize,
+) -> Result<VersionMetadataResponse, FetchError> {
+    let directory = cache_dir.join(module_name).join(version);
+    let body_path = directory.join("meta.json");
+    let last_modified_path = directory.join("meta.json.last-modified");
+
+    let cached_body = fs::read(&body_path).await.ok();
+    let cached_last_modified = fs::read_to_string(&last_modified_path).await.ok();
+
+    tracing::debug!("Fetching version {} for module {}.", version, module_name);
+    let mut request = client
+        .http()
+        .get(&version_metadata_url(module_name, version));
+    if let (Some(_), Some(last_modified)) = (&cached_body, &cached_last_modified) {
+        request = request.header(IF_MODIFIED_SINCE, last_modified.trim());
+    }
+    let response = check_retryable_status(send(client, request).await?)?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(body) = cached_body {
+            tracing::debug!("Using cached metadata for {}@{}.", module_name, version);
+            return Ok(serde_json::from_slice(&body)?);
+        }
+    }
+
+    // Deno returns a non-json content type if the module doesn't exist.
+    match response.headers().get(CONTENT_TYPE).map(|v| v.to_str()) {
+        Some(Ok("application/json")) => {}
+        _ => return Err(FetchError::MetadataNotPresent),
+    }
+
+    let last_modified = response
+        .headers()
+        .get(LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let body = read_body_with_limit(response, max_response_size as u64).await?;
+    let metadata = serde_json::from_slice(&body)?;
+
+    if let Some(last_modified) = last_modified {
+        fs::create_dir_all(&directory).await?;
+        fs::write(&body_path, &body).await?;
+        fs::write(&last_modified_path, last_modified).await?;
+    }
+
+    Ok(metadata)
+}
+
+/// Creates a link to the metadata of a version of a module.
+fn version_metadata_url(module_name: &str, version: &str) -> String {
+    format!(
+        "https://cdn.deno.land/{}/versions/{}/meta/meta.json",
+        module_name, version
+    )
+}
+
+/// Checks if the version of the module exists by issuing a `HEAD` request for its metadata.
+///
+/// Unlike [fetch_version_metadata], the metadata isn't downloaded or parsed, and a missing
+/// version is `Ok(false)` rather than [FetchError::MetadataNotPresent].
+pub async fn version_exists(
+    client: &impl RequestSender,
+    module_name: &str,
+    version: &str,
+) -> Result<bool, FetchError> {
+    tracing::debug!(
+        "Checking if version {} of module {} exists.",
+        version,
+        module_name
+    );
+    let request = client
+        .http()
+        .head(&version_metadata_url(module_name, version));
+    let response = send(client, request).await?;
+
+    match response.status() {
+        StatusCode::OK => Ok(true),
+        StatusCode::NOT_FOUND => Ok(false),
+        status => Err(FetchError::UnexpectedStatus(status)),
+    }
+}
+
+/// Downloads the tarball at the url.
+pub async fn fetch_tarball(client: &impl RequestSender, url: &str) -> Result<Bytes, FetchError> {
+    tracing::debug!("Downloading tarball from {}.", url);
+    let response =
+        check_rate_limit(send(client, client.http().get(url)).await?)?.error_for_status()?;
+    Ok(response.bytes().await?)
+}
+
+/// Downloads the tarball at the url straight to a file at `dest`, returning how many bytes were
+/// written, and aborting if it's larger than `limit` bytes.
+///
+/// The body is written as it's received, so the tarball is never held in memory all at once. If
+/// the download fails the partially written file is removed.
+pub async fn download_tarball_to_file(
+    client: &impl RequestSender,
+    url: &str,
+    dest: &Path,
+    limit: u64,
+) -> Result<u64, FetchError> {
+    tracing::debug!("Downloading tarball from {} to {}.", url, dest.display());
+    let response =
+        check_rate_limit(send(client, client.http().get(url)).await?)?.error_for_status()?;
+
+    if let Some(length) = response.content_length() {
+        if length > limit {
+            return Err(FetchError::TooLarge { limit });
+        }
+    }
+
+    let result = write_body_with_limit(response, dest, limit).await;
+    if result.is_err() {
+        if let Err(e) = fs::remove_file(dest).await {
+            tracing::warn!("Unable to remove partial tarball {}: {}", dest.display(), e);
+        }
+    }
+    result
+}
+
+/// Writes the body of the response to a file at `dest`, aborting once more than `limit` bytes
+/// have been received.
+async fn write_body_with_limit(
+    mut response: Response,
+    dest: &Path,
+    limit: u64,
+) -> Result<u64, FetchError> {
+    let mut file = fs::File::create(dest).await?;
+    let mut written = 0;
+    while let Some(chunk) = response.chunk().await? {
+        written += chunk.len() as u64;
+        if written > limit {
+            return Err(FetchError::TooLarge { limit });
+        }
+
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    Ok(written)
+}
+
+/// Downloads the tarball at the url, aborting if it's larger than `limit` bytes.
+pub async fn fetch_tarball_with_limit(
+    client: &impl RequestSender,
+    url: &str,
+    limit: u64,
+) -> Result<Bytes, FetchError> {
+    tracing::debug!(
+        "Downloading tarball from {} with a limit of {} bytes.",
+        url,
+        limit
+    );
+    let response =
+        check_rate_limit(send(client, client.http().get(url)).await?)?.error_for_status()?;
+    read_body_with_limit(response, limit).await
+}
+
+/// Reads the body of the response, aborting if it's larger than `limit` bytes.
+///
+/// The `Content-Length` header is checked before anything is read, but since it can't be
+/// trusted the size is also checked as the body is received.
+async fn read_body_with_limit(mut response: Response, limit: u64) -> Result<Bytes, FetchError> {
+    if let Some(length) = response.content_length() {
+        if length > limit {
+            return Err(FetchError::TooLarge { limit });
+        }
+    }
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if buffer.len() as u64 + chunk.len() as u64 > limit {
+            return Err(FetchError::TooLarge { limit });
+        }
+
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(buffer.into())
+}
+
+/// Downloads the tarballs at each of the urls, with at most `concurrency` downloads in flight at
+/// once.
+///
+/// The results are in the same order as the urls they were downloaded from.
+pub async fn batch_fetch_tarballs(
+    client: &Client,
+    urls: Vec<String>,
+    concurrency: usize,
+) -> Vec<Result<Bytes, FetchError>> {
+    let mut results: Vec<Option<Result<Bytes, FetchError>>> = urls.iter().map(|_| None).collect();
+    let mut urls = urls.into_iter().enumerate();
+    let mut in_flight = FuturesUnordered::new();
+
+    loop {
+        while in_flight.len() < concurrency.max(1) {
+            match urls.next() {
+                Some((index, url)) => in_flight.push(async move {
+                    let result = fetch_tarball(client, &url).await;
+                    (index, result)
+                }),
+                None => break,
+            }
+        }
+
+        match in_flight.next().await {
+            Some((index, result)) => results[index] = Some(result),
+            None => break,
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every url has been downloaded"))
+        .collect()
+}
+
 #[derive(Debug, Error)]
 pub enum FetchError {
     #[error("{0}")]
     HTTP(#[from] reqwest::Error),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
     #[error("resource has no metadata")]
     MetadataNotPresent,
+    #[error("uploads of type \"{0}\" can't be downloaded")]
+    UnsupportedUploadType(String),
+    #[error("unexpected response status {0}")]
+    UnexpectedStatus(StatusCode),
+    #[error("resource is larger than the limit of {limit} bytes")]
+    TooLarge { limit: u64 },
+    #[error("no registry to upload to has been configured")]
+    NoRegistry,
+    #[error("invalid npm specifier \"{0}\"")]
+    InvalidNpmSpecifier(String),
+    #[error("rate limited by the server")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("checksum mismatch, expected {expected} but got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("gave up after {attempts} attempts: {last_error}")]
+    ExhaustedRetries {
+        attempts: u8,
+        last_error: Box<FetchError>,
+    },
+}
+
+impl FetchError {
+    /// Gets the HTTP status code of the response that caused the error, if there was one.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            Self::HTTP(e) => e.status().map(|status| status.as_u16()),
+            Self::UnexpectedStatus(status) => Some(status.as_u16()),
+            Self::ExhaustedRetries { last_error, .. } => last_error.http_status(),
+            Self::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Gets how long the server asked to wait before retrying, if the request was rate limited.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            Self::ExhaustedRetries { last_error, .. } => last_error.retry_after(),
+            _ => None,
+        }
+    }
+
+    /// Checks if the error is likely to go away if the request is retried, such as timeouts or
+    /// server errors.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::HTTP(e) if e.is_timeout() || e.is_connect() => true,
+            _ => match self.http_status() {
+                Some(status) => status == 429 || status >= 500,
+                None => false,
+            },
+        }
+    }
+
+    /// Converts the error into a JSON object, so it can be embedded in JSON output instead of
+    /// being logged.
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+                "http_status": self.http_status(),
+                "is_transient": self.is_transient(),
+            }
+        })
+    }
+
+    /// Gets a machine-readable name for the kind of error.
+    fn code(&self) -> &'static str {
+        match self {
+            Self::HTTP(_) => "http",
+            Self::Io(_) => "io",
+            Self::Json(_) => "json",
+            Self::MetadataNotPresent => "metadata_not_present",
+            Self::UnsupportedUploadType(_) => "unsupported_upload_type",
+            Self::UnexpectedStatus(_) => "unexpected_status",
+            Self::TooLarge { .. } => "too_large",
+            Self::NoRegistry => "no_registry",
+            Self::InvalidNpmSpecifier(_) => "invalid_npm_specifier",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::ChecksumMismatch { .. } => "checksum_mismatch",
+            Self::ExhaustedRetries { .. } => "exhausted_retries",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload_options(upload_type: &str, repository: &str) -> UploadOptions {
+        UploadOptions {
+            upload_options_type: upload_type.to_string(),
+            upload_options_ref: "v1.0.0".to_string(),
+            repository: repository.to_string(),
+        }
+    }
+
+    #[test]
+    fn github_tarball_url() {
+        let options = upload_options(GITHUB_UPLOAD_TYPE, "denoland/deno_std");
+        assert_eq!(
+            options.tarball_url().unwrap(),
+            "https://api.github.com/repos/denoland/deno_std/tarball/v1.0.0"
+        );
+    }
+
+    #[test]
+    fn gitlab_tarball_url() {
+        let options = upload_options(GITLAB_UPLOAD_TYPE, "group/subgroup/project");
+        assert_eq!(
+            options.tarball_url().unwrap(),
+            "https://gitlab.com/group/subgroup/project/-/archive/v1.0.0/project-v1.0.0.tar.gz"
+        );
+    }
+
+    #[test]
+    fn url_tarball_url() {
+        let options = upload_options(URL_UPLOAD_TYPE, "https://example.com/module.tar.gz");
+        assert_eq!(
+            options.tarball_url().unwrap(),
+            "https://example.com/module.tar.gz"
+        );
+    }
+
+    #[test]
+    fn unsupported_upload_type() {
+        let options = upload_options("bitbucket", "owner/name");
+        match options.tarball_url() {
+            Err(FetchError::UnsupportedUploadType(upload_type)) => {
+                assert_eq!(upload_type, "bitbucket")
+            }
+            result => panic!("expected an unsupported upload type, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn hosts() {
+        let metadata = |upload_type: &str| VersionMetadataResponse {
+            upload_options: upload_options(upload_type, "owner/name"),
+            uploaded_at: None,
+            checksum: None,
+        };
+
+        let github = metadata(GITHUB_UPLOAD_TYPE);
+        assert!(github.is_github_hosted());
+        assert!(!github.is_gitlab_hosted());
+        assert!(!github.is_url_hosted());
+
+        let gitlab = metadata(GITLAB_UPLOAD_TYPE);
+        assert!(gitlab.is_gitlab_hosted());
+        assert!(!gitlab.is_github_hosted());
+
+        let url = metadata(URL_UPLOAD_TYPE);
+        assert!(url.is_url_hosted());
+        assert!(!url.is_github_hosted());
+    }
 }
@@ -0,0 +1,52 @@
+//! Downloads Deno modules from the registry and generates the doc nodes of their entry points.
+
+pub mod deno_archive;
+mod doh;
+pub mod fetch;
+pub mod npm;
+pub mod types;
+
+use deno_doc::DocParser;
+use reqwest::Client;
+
+pub use crate::{
+    deno_archive::{DenoArchive, DenoArchiveLoader},
+    fetch::{
+        fetch_tarball, fetch_version_metadata, fetch_versions_for_module, DenoModuleClient,
+        FetchError,
+    },
+    types::{DocInfo, GenerateError},
+};
+
+/// The file docs are parsed from if no `--entry` is provided.
+pub const DEFAULT_ENTRY: &str = "mod.ts";
+
+/// Downloads the version of the module, or its latest version if there isn't one, and parses
+/// the doc nodes of its `mod.ts`.
+pub async fn generate_doc_info(
+    client: &Client,
+    module: &str,
+    version: Option<&str>,
+) -> Result<DocInfo, GenerateError> {
+    let version = match version {
+        Some(version) => version.to_string(),
+        None => fetch_versions_for_module(client, module).await?.latest,
+    };
+
+    let mut archive = DenoArchive::from_url(client, module, &version).await?;
+    let root_directory = archive
+        .root_directory()?
+        .ok_or(GenerateError::EmptyArchive)?;
+
+    let loader: DenoArchiveLoader = archive.into();
+    let doc_parser = DocParser::new(Box::new(loader), false);
+    let nodes = doc_parser
+        .parse(&format!("{}/{}", root_directory, DEFAULT_ENTRY))
+        .await?;
+
+    Ok(DocInfo {
+        module_name: module.to_string(),
+        version,
+        nodes,
+    })
+}
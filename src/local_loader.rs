@@ -0,0 +1,130 @@
+//! Loads the files of a Deno project from a directory on disk instead of a downloaded archive.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use deno_doc::{parser::DocFileLoader, DocError};
+use deno_doc_info_generator::deno_archive;
+use futures::future::LocalBoxFuture;
+use reqwest::Url;
+use swc_ecmascript::parser::{Syntax, TsConfig};
+use tracing::{Instrument, Span};
+
+/// Loads files from a root directory on disk, which files can't import anything outside of.
+#[derive(Debug, Clone)]
+pub struct LocalFileSystemLoader {
+    root: PathBuf,
+    /// The parser options used for every file, on top of which the extension's are enabled.
+    ts_config: TsConfig,
+}
+
+impl LocalFileSystemLoader {
+    /// Creates a loader for the files in the directory.
+    pub fn new<P>(root: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self {
+            root: fs::canonicalize(root)?,
+            ts_config: TsConfig::default(),
+        })
+    }
+
+    /// Parses every file with the options along with the ones needed by the extension of the file.
+    pub fn with_ts_config(mut self, ts_config: TsConfig) -> Self {
+        self.ts_config = ts_config;
+        self
+    }
+
+    /// Gets the canonical path of the root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Counts the files in the root directory and their total size in bytes.
+    pub fn file_totals(&self) -> io::Result<(usize, u64)> {
+        let mut totals = (0, 0);
+        add_file_totals(&self.root, &mut totals)?;
+        Ok(totals)
+    }
+}
+
+impl DocFileLoader for LocalFileSystemLoader {
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, DocError> {
+        if specifier.starts_with("https://") {
+            return Ok(specifier.to_string());
+        }
+
+        tracing::debug!("Resolving {} referred to by {}", specifier, referrer);
+
+        // Relative imports in remote modules are relative to the url they were loaded from.
+        if referrer.starts_with("https://") {
+            return Url::parse(referrer)
+                .and_then(|url| url.join(specifier))
+                .map(String::from)
+                .map_err(|e| DocError::Resolve(format!("{}: {}", specifier, e)));
+        }
+
+        // Specifiers starting with `/` are relative to the root directory.
+        let path = match specifier.strip_prefix('/') {
+            Some(specifier) => self.root.join(specifier),
+            None => Path::new(referrer)
+                .parent()
+                .unwrap_or(&self.root)
+                .join(specifier),
+        };
+
+        let path = fs::canonicalize(&path)
+            .map_err(|e| DocError::Resolve(format!("{}: {}", path.display(), e)))?;
+        if !path.starts_with(&self.root) {
+            return Err(DocError::Resolve(format!(
+                "{} imported by {} is outside of {}",
+                specifier,
+                referrer,
+                self.root.display()
+            )));
+        }
+
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    // The span is attached to the returned future so it covers the whole load.
+    #[tracing::instrument(skip(self))]
+    fn load_source_code(
+        &self,
+        specifier: &str,
+    ) -> LocalBoxFuture<Result<(Syntax, String), DocError>> {
+        tracing::debug!("Loading {} from disk", specifier);
+
+        let syntax = deno_archive::syntax_for_path_with(specifier, self.ts_config);
+        let specifier = specifier.to_string();
+        Box::pin(
+            async move {
+                let source = tokio::fs::read_to_string(&specifier)
+                    .await
+                    .map_err(DocError::Io)?;
+                Ok((syntax, source))
+            }
+            .instrument(Span::current()),
+        )
+    }
+}
+
+/// Adds the count and size of the files in the directory and its subdirectories to the totals.
+fn add_file_totals(directory: &Path, totals: &mut (usize, u64)) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            add_file_totals(&entry.path(), totals)?;
+        } else if file_type.is_file() {
+            totals.0 += 1;
+            totals.1 += entry.metadata()?.len();
+        }
+    }
+
+    Ok(())
+}
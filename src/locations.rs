@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use deno_doc::{parser::DocFileLoader, DocNode};
+use swc_common::{BytePos, Spanned};
+use swc_ecmascript::parser::{Parser, StringInput, Syntax};
+
+/// Computes the byte range of the declaration of every doc node in its source file, keyed by
+/// the index of the node.
+///
+/// The start comes from the line and column of the node's location, and the end from the
+/// top-level statement containing it, so the sources are loaded again through the loader that
+/// was used for parsing. If the source can't be parsed the range ends at the end of the line.
+pub async fn analyze(
+    loader: &dyn DocFileLoader,
+    nodes: &[DocNode],
+) -> HashMap<usize, (usize, usize)> {
+    let mut files: HashMap<&str, Option<(String, Vec<(usize, usize)>)>> = HashMap::new();
+    let mut ranges = HashMap::new();
+
+    for (index, node) in nodes.iter().enumerate() {
+        let filename = node.location.filename.as_str();
+        if !files.contains_key(filename) {
+            let file = match loader.load_source_code(filename).await {
+                Ok((syntax, source)) => {
+                    let items = item_spans(syntax, &source).unwrap_or_default();
+                    Some((source, items))
+                }
+                Err(e) => {
+                    tracing::warn!("Unable to load {} to find locations: {}", filename, e);
+                    None
+                }
+            };
+            files.insert(filename, file);
+        }
+
+        let (source, items) = match &files[filename] {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if let Some(start) = byte_offset(source, node.location.line, node.location.col) {
+            let end = items
+                .iter()
+                .find(|(lo, hi)| *lo <= start && start < *hi)
+                .map(|(_, hi)| *hi)
+                .unwrap_or_else(|| {
+                    source[start..]
+                        .find('\n')
+                        .map_or(source.len(), |length| start + length)
+                });
+            ranges.insert(index, (start, end));
+        }
+    }
+
+    ranges
+}
+
+/// Parses the source and gets the byte range of each top-level statement, returning [None] if
+/// the source couldn't be parsed.
+fn item_spans(syntax: Syntax, source: &str) -> Option<Vec<(usize, usize)>> {
+    let input = StringInput::new(source, BytePos(0), BytePos(source.len() as u32));
+    let module = Parser::new(syntax, input, None).parse_module().ok()?;
+
+    let spans = module
+        .body
+        .iter()
+        .map(|item| {
+            let span = item.span();
+            (span.lo.0 as usize, span.hi.0 as usize)
+        })
+        .collect();
+    Some(spans)
+}
+
+/// Converts a 1-based line and a column counted in characters into a byte offset, returning
+/// [None] if the position is outside of the source.
+fn byte_offset(source: &str, line: usize, col: usize) -> Option<usize> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        source
+            .match_indices('\n')
+            .nth(line - 2)
+            .map(|(index, _)| index + 1)?
+    };
+
+    let line_text = &source[line_start..];
+    let column = line_text
+        .char_indices()
+        .nth(col)
+        .map_or(line_text.len(), |(index, _)| index);
+
+    Some(line_start + column)
+}
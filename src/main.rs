@@ -1,66 +1,1082 @@
-mod deno_archive;
-mod fetch;
+mod cache;
+mod cli;
+mod complexity;
+mod diff;
+mod doc_node;
+mod export_map;
+mod local_loader;
+mod locations;
+mod output;
+mod profile;
+#[cfg(test)]
+mod test_util;
 
-use std::{env, io::Cursor};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufWriter, Cursor, Write},
+    path::Path,
+    process,
+};
 
-use deno_archive::{DenoArchive, DenoArchiveLoader};
-use deno_doc::DocParser;
-use reqwest::{redirect::Policy, ClientBuilder};
+use chrono::{DateTime, Utc};
+use deno_doc::{parser::DocFileLoader, DocNode, DocNodeKind, DocParser};
+use deno_doc_info_generator::{
+    deno_archive::{self, DenoArchive, DenoArchiveLoader},
+    fetch::{DenoModuleClient, FetchError, RetryPolicy},
+    npm::NpmResolver,
+};
+use futures::stream::{self, StreamExt};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
-use crate::fetch::FetchError;
+use crate::{
+    cache::TarballCache,
+    cli::Options,
+    doc_node::DocNodeExt,
+    local_loader::LocalFileSystemLoader,
+    output::{changelog, ModuleDocs, OutputError, OutputFormat},
+};
 
 #[cfg(not(debug_assertions))]
 const DEFAULT_LOG_FILTER: &'static str = "deno_doc_info_generator=info,error";
 #[cfg(debug_assertions)]
 const DEFAULT_LOG_FILTER: &'static str = "deno_doc_info_generator=debug";
 
+/// The version given to archives read from stdin if one isn't provided, since there's no metadata
+/// to get it from.
+const STDIN_VERSION: &str = "unknown";
+
+/// The version given to projects loaded from disk with `--local` if one isn't provided.
+const LOCAL_VERSION: &str = "local";
+
+/// The environment variable holding the token used to authenticate with the registry.
+const REGISTRY_TOKEN_VAR: &str = "DENO_REGISTRY_TOKEN";
+
 #[tokio::main]
 async fn main() {
-    // Sets the default logger predicate.
-    env::set_var(
-        "RUST_LOG",
-        env::var("RUST_LOG").unwrap_or(DEFAULT_LOG_FILTER.into()),
-    );
+    let options = Options::from_args();
 
-    pretty_env_logger::init();
+    // The default filter is used if RUST_LOG isn't set, and --trace prints how long each
+    // instrumented operation took when it finishes.
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let span_events = if options.trace {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(span_events)
+        .init();
 
-    let client = ClientBuilder::new()
-        .redirect(Policy::default())
-        .user_agent("deno-doc-info-generator")
-        .build()
-        .unwrap();
+    if let Some(shell) = options.generate_completions {
+        let stdout = io::stdout();
+        if let Err(e) = cli::write_completions(shell, stdout.lock()) {
+            tracing::error!("Unable to write completions: {}", e);
+        }
+        return;
+    }
 
-    // TODO: make this configurable potentially through an env var.
-    let module = "channo";
+    if let Some(root) = &options.local {
+        return generate_local_docs(&options, root).await;
+    }
 
-    let versions = match fetch::fetch_versions_for_module(&client, module).await {
-        Ok(v) => v,
-        Err(FetchError::MetadataNotPresent) => return log::error!("Module not found"),
-        Err(e) => return log::error!("{}", e),
-    };
-    let version_metadata =
-        match fetch::fetch_version_metadata(&client, module, &versions.latest).await {
-            Ok(v) => v,
-            Err(FetchError::MetadataNotPresent) => return log::error!("Version not found"),
-            Err(e) => return log::error!("{}", e),
+    let mut client_builder = DenoModuleClient::builder().retry_policy(RetryPolicy::from_env());
+    if let Some(cache_dir) = &options.cache_dir {
+        client_builder = client_builder.cache_dir(cache_dir);
+    }
+    if let Some(registry) = &options.registry {
+        client_builder = client_builder.registry_url(registry);
+    }
+    let client = client_builder.build().unwrap();
+
+    if let Some(path) = &options.modules_file {
+        return process_modules_file(&client, &options, path).await;
+    }
+
+    let module = match &options.module {
+        Some(module) => module.as_str(),
+        None => return tracing::error!("Usage: deno_doc_info_generator <module> [version]"),
+    };
+
+    if let Some(tarball) = &options.upload {
+        return upload_tarball(&client, module, tarball).await;
+    }
+
+    if options.list_versions {
+        return list_versions(&client, module).await;
+    }
+
+    if let Some([from_version, to_version]) = options.diff.as_deref() {
+        return write_version_diff(&client, &options, module, from_version, to_version).await;
+    }
+
+    let version = if options.since.is_some() || options.until.is_some() {
+        match version_in_range(
+            &client,
+            module,
+            options.since,
+            options.until,
+            options.concurrency(),
+        )
+        .await
+        {
+            Some(version) => version,
+            None => return,
+        }
+    } else {
+        options.version().to_string()
+    };
+
+    let max_archive_size = options.max_archive_size_bytes();
+    let mut archive = if options.stdin {
+        let version = options.version.as_deref().unwrap_or(STDIN_VERSION);
+        match DenoArchive::from_stdin(module.into(), version.into()) {
+            Ok(archive) => archive,
+            Err(e) => return tracing::error!("Unable to read archive from stdin: {}", e),
+        }
+    } else {
+        // Errors are written as JSON too when the output is JSON, so pipelines always get some.
+        let json_errors = options.output_format().is_json();
+        let cache = if options.no_cache {
+            None
+        } else {
+            TarballCache::from_env()
         };
 
-    let url = version_metadata.upload_options.tarball_url().unwrap();
-    let bytes = client.get(url).send().await.unwrap().bytes().await.unwrap();
-    let reader = Cursor::new(bytes.to_vec());
+        let download = DownloadOptions {
+            cache: cache.as_ref(),
+            save_to: options.save_tarball.as_deref(),
+            max_archive_size,
+            verify_checksum: !options.skip_verify,
+            json_errors,
+        };
+
+        match download_archive(&client, module, &version, download).await {
+            Some(archive) => archive,
+            None => return,
+        }
+    };
 
-    let mut archive = DenoArchive::from_reader("channo".into(), "0.1.1".into(), reader)
-        .expect("unable to decode archive");
     let root_directory = archive.root_directory().unwrap().unwrap();
 
-    log::debug!("Root directory of archive is \"{}\"", &root_directory);
+    tracing::debug!("Root directory of archive is \"{}\"", &root_directory);
 
-    let file_loader: DenoArchiveLoader = archive.into();
-    let doc_parser = DocParser::new(Box::new(file_loader), false);
+    if options.list_files {
+        match archive.list_typescript_files() {
+            Ok(paths) => paths.iter().for_each(|path| println!("{}", path)),
+            Err(e) => tracing::error!("Unable to list files: {}", e),
+        }
+        return;
+    }
 
-    let res = doc_parser
-        .parse(&format!("{}/mod.ts", root_directory))
-        .await
+    if let Some(dest) = &options.extract {
+        if let Err(e) = archive.extract_to_dir(dest) {
+            tracing::error!("Unable to extract archive to {}: {}", dest.display(), e);
+        }
+        return;
+    }
+
+    if let Some(file) = &options.cat {
+        let path = format!("{}/{}", root_directory, file.trim_start_matches('/'));
+        if let Err(e) = archive.cat(&path) {
+            tracing::error!("Unable to read {}: {}", file, e);
+        }
+        return;
+    }
+
+    let deno_toml = format!("{}/Deno.toml", root_directory);
+    match archive.read_toml_file::<toml::Value>(&deno_toml) {
+        Ok(config) => tracing::debug!("Found Deno.toml: {}", config),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Unable to parse Deno.toml: {}", e),
+    }
+
+    if options.export_map {
+        let exports = match export_map::export_map(&mut archive, &root_directory) {
+            Ok(exports) => exports,
+            Err(e) => return tracing::error!("Unable to create export map: {}", e),
+        };
+
+        let map = serde_json::json!({ "exports": exports });
+        return println!("{}", serde_json::to_string_pretty(&map).unwrap());
+    }
+
+    let (file_count, total_bytes) = match archive.file_totals() {
+        Ok(totals) => totals,
+        Err(e) => return tracing::error!("Unable to read archive: {}", e),
+    };
+
+    if options.stats {
+        match archive.count_lines_of_code() {
+            Ok(lines) => tracing::info!(
+                "Found {} lines of TypeScript ({} code, {} comment, {} blank)",
+                lines.total,
+                lines.code,
+                lines.comment,
+                lines.blank
+            ),
+            Err(e) => tracing::error!("Unable to count lines of code: {}", e),
+        }
+    }
+
+    let version = archive.version.clone();
+    let file_loader = archive_loader(&client, &options, archive);
+    let doc_parser = DocParser::new(Box::new(file_loader.clone()), options.private);
+
+    let entry_points: Vec<String> = options
+        .entries()
+        .iter()
+        .map(|entry| format!("{}/{}", root_directory, entry.trim_start_matches('/')))
+        .collect();
+
+    let res = match parse_entry_points(&doc_parser, &entry_points).await {
+        Some(nodes) => nodes,
+        None => return,
+    };
+
+    if let Some(path) = &options.profile_output {
+        let events = file_loader.load_events().await;
+        if let Err(e) = profile::write_chrome_trace(&events, path) {
+            tracing::error!("Unable to write profile to {}: {}", path.display(), e);
+        }
+    }
+
+    if options.stats {
+        let stats = file_loader.stats_summary().await;
+        tracing::info!(
+            "Loaded {} files ({} cache hits, {} cache misses) and read {} bytes",
+            stats.total_loads,
+            stats.cache_hits,
+            stats.cache_misses,
+            stats.total_bytes_read
+        );
+        tracing::info!(
+            "Found {} async functions",
+            DocNode::async_functions(&res).len()
+        );
+    }
+
+    // Only the catalog has the metadata of the module, so the tags aren't fetched otherwise.
+    let tags = if options.output_format() == OutputFormat::Catalog {
+        client.fetch_module_tags(module).await.unwrap_or_else(|e| {
+            tracing::warn!("Unable to fetch tags of {}: {}", module, e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    let docs = ModuleDocs {
+        module_name: module.to_string(),
+        version,
+        nodes: res,
+        entry_point: entry_points[0].clone(),
+        file_count,
+        total_bytes,
+        tags,
+        ..ModuleDocs::default()
+    };
+
+    finish_docs(&options, &file_loader, docs, &entry_points).await;
+}
+
+/// Generates docs for a project in a directory on disk, without using the network.
+async fn generate_local_docs(options: &Options, root: &Path) {
+    let loader = match LocalFileSystemLoader::new(root) {
+        Ok(loader) => loader.with_ts_config(options.ts_config()),
+        Err(e) => return tracing::error!("Unable to open {}: {}", root.display(), e),
+    };
+
+    let (file_count, total_bytes) = match loader.file_totals() {
+        Ok(totals) => totals,
+        Err(e) => return tracing::error!("Unable to read {}: {}", root.display(), e),
+    };
+
+    let module_name = match &options.module {
+        Some(module) => module.clone(),
+        None => loader
+            .root()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let version = options.version.as_deref().unwrap_or(LOCAL_VERSION);
+
+    let entry_points: Vec<String> = options
+        .entries()
+        .iter()
+        .map(|entry| {
+            let path = loader.root().join(entry.trim_start_matches('/'));
+            path.to_string_lossy().into_owned()
+        })
+        .collect();
+
+    let doc_parser = DocParser::new(Box::new(loader.clone()), options.private);
+    let nodes = match parse_entry_points(&doc_parser, &entry_points).await {
+        Some(nodes) => nodes,
+        None => return,
+    };
+
+    let docs = ModuleDocs {
+        module_name,
+        version: version.to_string(),
+        nodes,
+        entry_point: entry_points[0].clone(),
+        file_count,
+        total_bytes,
+        ..ModuleDocs::default()
+    };
+
+    finish_docs(options, &loader, docs, &entry_points).await;
+}
+
+/// Creates a loader for the archive configured by the options.
+fn archive_loader(
+    client: &DenoModuleClient,
+    options: &Options,
+    archive: DenoArchive,
+) -> DenoArchiveLoader {
+    let mut file_loader = DenoArchiveLoader::from(archive).with_ts_config(options.ts_config());
+    if options.allow_npm {
+        file_loader = file_loader.with_npm_resolver(NpmResolver::new(client.clone()));
+    }
+    for (from, to) in &options.aliases {
+        file_loader = file_loader.with_alias(from.clone(), to.clone());
+    }
+    file_loader
+}
+
+/// Parses the doc nodes of every entry point, logging the error if one can't be parsed.
+async fn parse_entry_points(
+    doc_parser: &DocParser,
+    entry_points: &[String],
+) -> Option<Vec<DocNode>> {
+    let mut res: Vec<DocNode> = Vec::new();
+    for entry_point in entry_points {
+        let nodes = match doc_parser.parse(entry_point).await {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                tracing::error!("Unable to parse {}: {}", entry_point, e);
+                return None;
+            }
+        };
+
+        // Entry points often re-export each other, so the same symbol can be parsed twice. Only
+        // the nodes of previous entry points are compared so the overloads of functions are kept.
+        let previous = res.len();
+        for node in nodes {
+            if !res[..previous]
+                .iter()
+                .any(|existing| existing.name == node.name && existing.kind == node.kind)
+            {
+                res.push(node);
+            }
+        }
+    }
+    tracing::debug!("Found {} doc items", res.len());
+
+    Some(res)
+}
+
+/// Keeps the nodes of the kind, along with the module doc since it's where every format gets the
+/// module description from.
+fn retain_kind(nodes: &mut Vec<DocNode>, kind: &DocNodeKind) {
+    nodes.retain(|node| node.kind == *kind || node.kind == DocNodeKind::ModuleDoc);
+}
+
+/// Gets the code to exit with if there are no doc nodes and the options fail on that, ignoring the
+/// module doc since it's kept by every filter.
+fn empty_docs_error(options: &Options, nodes: &[DocNode]) -> Option<i32> {
+    let is_empty = nodes.iter().all(|node| node.kind == DocNodeKind::ModuleDoc);
+
+    if is_empty && options.fails_on_empty() {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Filters and analyzes the parsed docs according to the options, then writes them and checks
+/// the assertions made about them, exiting with an error code if they fail.
+async fn finish_docs(
+    options: &Options,
+    loader: &dyn DocFileLoader,
+    mut docs: ModuleDocs,
+    entry_points: &[String],
+) {
+    if options.skip_external {
+        docs.nodes.retain(|node| {
+            let filename = &node.location.filename;
+            !filename.starts_with("https://") && !filename.starts_with("http://")
+        });
+        tracing::debug!("Kept {} doc items defined in the module", docs.nodes.len());
+    }
+
+    if let Some(kind) = &options.kind {
+        retain_kind(&mut docs.nodes, kind);
+        tracing::debug!("Kept {} doc items of kind {:?}", docs.nodes.len(), kind);
+    }
+
+    if let Some(interface) = &options.implements {
+        docs.nodes.retain(|node| {
+            node.implements_interface(interface) || node.kind == DocNodeKind::ModuleDoc
+        });
+        tracing::debug!(
+            "Kept {} doc items implementing {}",
+            docs.nodes.len(),
+            interface
+        );
+    }
+
+    if let Some(code) = empty_docs_error(options, &docs.nodes) {
+        tracing::error!("No doc nodes were parsed from {}", entry_points.join(", "));
+        process::exit(code);
+    }
+
+    if options.output_format() == OutputFormat::JsonWithLocations {
+        docs.byte_ranges = locations::analyze(loader, &docs.nodes).await;
+    }
+
+    if options.analyze_complexity {
+        docs.complexity = complexity::analyze(loader, &docs.nodes).await;
+    }
+
+    let stdout = io::stdout();
+    let writer: Box<dyn Write> = match &options.output_file {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                tracing::error!("Unable to create {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(stdout.lock()),
+    };
+
+    if let Err(e) = output::write_docs(
+        options.output_format(),
+        &docs,
+        &options.output_options,
+        writer,
+    ) {
+        tracing::error!("Unable to write output: {}", e);
+        process::exit(1);
+    }
+
+    let missing_symbols: Vec<&String> = options
+        .assert_symbols
+        .iter()
+        .filter(|symbol| !docs.nodes.iter().any(|node| node.name == **symbol))
+        .collect();
+
+    for symbol in &missing_symbols {
+        tracing::error!("Asserted symbol \"{}\" was not found", symbol);
+    }
+
+    let deprecated: Vec<&DocNode> = if options.assert_no_deprecated {
+        docs.nodes
+            .iter()
+            .filter(|node| node.is_deprecated())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    for node in &deprecated {
+        tracing::error!(
+            "Symbol \"{}\" is deprecated ({}:{})",
+            node.name,
+            node.location.filename,
+            node.location.line
+        );
+    }
+
+    if !missing_symbols.is_empty() || !deprecated.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// How [`download_archive`] gets and checks the tarball of a version.
+struct DownloadOptions<'a> {
+    /// The cache the tarball is read from if it's been downloaded before, and written to otherwise.
+    cache: Option<&'a TarballCache>,
+    /// Where the tarball is saved, if anywhere.
+    save_to: Option<&'a Path>,
+    /// The largest the tarball and its unpacked files may be, in bytes.
+    max_archive_size: u64,
+    /// Whether the tarball is checked against the checksum in the version metadata.
+    verify_checksum: bool,
+    /// Whether errors from fetching are also written to stdout as JSON.
+    json_errors: bool,
+}
+
+/// Downloads the archive of the version of the module, logging why if it can't be.
+///
+/// Only verified tarballs are cached, and cached ones are verified again when they're read.
+async fn download_archive(
+    client: &DenoModuleClient,
+    module: &str,
+    version: &str,
+    download: DownloadOptions<'_>,
+) -> Option<DenoArchive> {
+    let DownloadOptions {
+        cache,
+        save_to,
+        max_archive_size,
+        verify_checksum,
+        json_errors,
+    } = download;
+
+    let report = |context: &str, e: &FetchError| {
+        match e {
+            FetchError::MetadataNotPresent => tracing::error!("{}", context),
+            _ => log_fetch_error(context, e),
+        }
+
+        if json_errors {
+            println!("{}", e.to_json_value());
+        }
+    };
+
+    let version = if version == cli::LATEST_VERSION {
+        match client.fetch_versions_for_module(module).await {
+            Ok(v) => v.latest,
+            Err(e @ FetchError::MetadataNotPresent) => {
+                report(&format!("Module \"{}\" not found", module), &e);
+                return None;
+            }
+            Err(e) => {
+                report("Unable to fetch versions", &e);
+                return None;
+            }
+        }
+    } else {
+        version.to_string()
+    };
+    let version_metadata = match client.fetch_version_metadata(module, &version).await {
+        Ok(v) => v,
+        Err(e @ FetchError::MetadataNotPresent) => {
+            report(
+                &format!("Version {} of module \"{}\" not found", version, module),
+                &e,
+            );
+            return None;
+        }
+        Err(e) => {
+            report("Unable to fetch version metadata", &e);
+            return None;
+        }
+    };
+
+    let verify = |bytes: &[u8]| match (verify_checksum, &version_metadata.checksum) {
+        (true, Some(expected)) => deno_archive::verify_sha256(bytes, expected).map_err(|actual| {
+            FetchError::ChecksumMismatch {
+                expected: expected.clone(),
+                actual,
+            }
+        }),
+        _ => Ok(()),
+    };
+
+    let cached = match cache {
+        Some(cache) => cache.get(module, &version).await.unwrap_or_else(|e| {
+            tracing::warn!("Unable to read cached archive: {}", e);
+            None
+        }),
+        None => None,
+    };
+    let cached = cached.filter(|bytes| match verify(bytes) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(
+                "Downloading the archive again since the cached one is invalid: {}",
+                e
+            );
+            false
+        }
+    });
+
+    let bytes = match cached {
+        Some(bytes) => {
+            tracing::debug!("Using cached archive for {}@{}", module, version);
+
+            if let Some(path) = save_to {
+                if let Err(e) = tokio::fs::write(path, &bytes).await {
+                    tracing::error!("Unable to save archive to {}: {}", path.display(), e);
+                    return None;
+                }
+            }
+
+            bytes
+        }
+        None => {
+            let url = match version_metadata.upload_options.tarball_url() {
+                Ok(url) => url,
+                Err(e) => {
+                    report("Unable to download archive", &e);
+                    return None;
+                }
+            };
+            let bytes = match save_to {
+                Some(path) => download_to_file(client, &url, path, max_archive_size).await,
+                None => client
+                    .fetch_tarball_with_limit(&url, max_archive_size)
+                    .await
+                    .map(|bytes| bytes.to_vec()),
+            };
+            let bytes = match bytes {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    report("Unable to download archive", &e);
+                    return None;
+                }
+            };
+
+            if let Err(e) = verify(&bytes) {
+                report("Unable to verify archive", &e);
+                return None;
+            }
+
+            if let (true, Some(cache)) = (verify_checksum, cache) {
+                if let Err(e) = cache.put(module, &version, &bytes).await {
+                    tracing::warn!("Unable to cache archive: {}", e);
+                }
+            }
+
+            bytes
+        }
+    };
+    let reader = Cursor::new(bytes);
+
+    match DenoArchive::from_reader_with_limit(module.into(), version, reader, max_archive_size) {
+        Ok(archive) => Some(archive),
+        Err(e) => {
+            tracing::error!("Unable to decode archive: {}", e);
+            None
+        }
+    }
+}
+
+/// Downloads the tarball at the url to the path, aborting if it's larger than `limit` bytes, then
+/// reads it back.
+async fn download_to_file(
+    client: &DenoModuleClient,
+    url: &str,
+    path: &Path,
+    limit: u64,
+) -> Result<Vec<u8>, FetchError> {
+    let written = client.download_tarball_to_file(url, path, limit).await?;
+    tracing::debug!("Saved {} bytes to {}", written, path.display());
+
+    Ok(tokio::fs::read(path).await?)
+}
+
+/// Uploads the tarball to the registry, taking the version from its `{version}.tar.gz` file name.
+async fn upload_tarball(client: &DenoModuleClient, module: &str, tarball: &Path) {
+    let token = match env::var(REGISTRY_TOKEN_VAR) {
+        Ok(token) => token,
+        Err(_) => return tracing::error!("{} must be set to upload a module", REGISTRY_TOKEN_VAR),
+    };
+
+    let version = match tarball
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".tar.gz"))
+    {
+        Some(version) => version,
+        None => return tracing::error!("Unable to get the version from {}", tarball.display()),
+    };
+
+    match client.upload_module(module, version, tarball, &token).await {
+        Ok(()) => tracing::info!("Uploaded {}@{}", module, version),
+        Err(e) => log_fetch_error("Unable to upload module", &e),
+    }
+}
+
+/// Finds the newest version of the module published between the dates, logging why if there
+/// isn't one.
+async fn version_in_range(
+    client: &DenoModuleClient,
+    module: &str,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    concurrency: usize,
+) -> Option<String> {
+    let versions = match client.fetch_versions_for_module(module).await {
+        Ok(versions) => versions,
+        Err(e @ FetchError::MetadataNotPresent) => {
+            log_fetch_error(&format!("Module \"{}\" not found", module), &e);
+            return None;
+        }
+        Err(e) => {
+            log_fetch_error("Unable to fetch versions", &e);
+            return None;
+        }
+    };
+
+    let metadata = client
+        .fetch_all_version_metadata(module, &versions.versions, concurrency)
+        .await;
+
+    // The versions are listed newest first.
+    let version = metadata.into_iter().find_map(|(version, metadata)| {
+        let uploaded_at = metadata.uploaded_at?;
+        let in_range = since.is_none_or(|since| uploaded_at >= since)
+            && until.is_none_or(|until| uploaded_at <= until);
+        Some(version).filter(|_| in_range)
+    });
+
+    match version {
+        Some(version) => tracing::debug!("Using version {} from the date range.", version),
+        None => tracing::error!("No versions of {} were published in the date range", module),
+    }
+    version
+}
+
+/// Writes the changes to the exported symbols of the module between the versions as a changelog.
+async fn write_version_diff(
+    client: &DenoModuleClient,
+    options: &Options,
+    module: &str,
+    from_version: &str,
+    to_version: &str,
+) {
+    let older = match parse_version_docs(client, options, module, from_version).await {
+        Some(docs) => docs,
+        None => return,
+    };
+    let newer = match parse_version_docs(client, options, module, to_version).await {
+        Some(docs) => docs,
+        None => return,
+    };
+
+    let changes = diff::diff_versions(&older.nodes, &newer.nodes).into_changes(
+        module,
+        from_version,
+        to_version,
+    );
+    let stdout = io::stdout();
+    if let Err(e) = changelog::write(
+        &changes,
+        options.output_options.changelog_format,
+        stdout.lock(),
+    ) {
+        tracing::error!("Unable to write diff: {}", e);
+    }
+}
+
+/// Downloads the version of the module and parses the doc nodes of its entry points.
+async fn parse_version_docs(
+    client: &DenoModuleClient,
+    options: &Options,
+    module: &str,
+    version: &str,
+) -> Option<ModuleDocs> {
+    let cache = if options.no_cache {
+        None
+    } else {
+        TarballCache::from_env()
+    };
+    let download = DownloadOptions {
+        cache: cache.as_ref(),
+        save_to: None,
+        max_archive_size: options.max_archive_size_bytes(),
+        verify_checksum: !options.skip_verify,
+        json_errors: false,
+    };
+    let mut archive = download_archive(client, module, version, download).await?;
+
+    let root_directory = match archive.root_directory() {
+        Ok(Some(root_directory)) => root_directory,
+        Ok(None) => {
+            tracing::error!("The archive of {}@{} is empty", module, version);
+            return None;
+        }
+        Err(e) => {
+            tracing::error!("Unable to read archive of {}@{}: {}", module, version, e);
+            return None;
+        }
+    };
+
+    let entry_points: Vec<String> = options
+        .entries()
+        .iter()
+        .map(|entry| format!("{}/{}", root_directory, entry.trim_start_matches('/')))
+        .collect();
+
+    let (file_count, total_bytes) = match archive.file_totals() {
+        Ok(totals) => totals,
+        Err(e) => {
+            tracing::error!("Unable to read archive of {}@{}: {}", module, version, e);
+            return None;
+        }
+    };
+
+    // The archive has the version "latest" was resolved to.
+    let version = archive.version.clone();
+    let file_loader = archive_loader(client, options, archive);
+    let doc_parser = DocParser::new(Box::new(file_loader), options.private);
+    let nodes = parse_entry_points(&doc_parser, &entry_points).await?;
+
+    Some(ModuleDocs {
+        module_name: module.to_string(),
+        version,
+        nodes,
+        entry_point: entry_points[0].clone(),
+        file_count,
+        total_bytes,
+        ..ModuleDocs::default()
+    })
+}
+
+/// Generates the JSON docs of every `module[@version]` listed in the file, writing them to
+/// `{out_dir}/{module}/{version}.json` with at most `--concurrency` modules at once.
+///
+/// Exits with an error code if any of the modules failed.
+async fn process_modules_file(client: &DenoModuleClient, options: &Options, path: &Path) {
+    let out_dir = match &options.output_options.out_dir {
+        Some(out_dir) => out_dir,
+        None => return tracing::error!("--modules-file requires --out-dir"),
+    };
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => return tracing::error!("Unable to read {}: {}", path.display(), e),
+    };
+
+    let modules: Vec<(&str, &str)> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.splitn(2, '@');
+            let module = parts.next().unwrap_or(line);
+            (module, parts.next().unwrap_or(cli::LATEST_VERSION))
+        })
+        .collect();
+
+    let results: Vec<bool> = stream::iter(&modules)
+        .map(|(module, version)| write_module_json(client, options, out_dir, module, version))
+        .buffer_unordered(options.concurrency())
+        .collect()
+        .await;
+
+    let failed = results.iter().filter(|succeeded| !**succeeded).count();
+    if failed > 0 {
+        tracing::error!(
+            "Unable to generate docs for {} of {} modules",
+            failed,
+            modules.len()
+        );
+        process::exit(1);
+    }
+
+    tracing::info!("Generated docs for {} modules", modules.len());
+}
+
+/// Generates the JSON docs of the version of the module in the output directory, returning
+/// whether it succeeded.
+async fn write_module_json(
+    client: &DenoModuleClient,
+    options: &Options,
+    out_dir: &Path,
+    module: &str,
+    version: &str,
+) -> bool {
+    let mut tags = Vec::new();
+    if let Some(tag) = &options.filter_tag {
+        tags = match client.fetch_module_tags(module).await {
+            Ok(tags) => tags,
+            Err(e) => {
+                log_fetch_error(&format!("Unable to fetch tags of {}", module), &e);
+                return false;
+            }
+        };
+
+        if !tags.contains(tag) {
+            tracing::debug!("Skipping {} since it isn't tagged {}", module, tag);
+            return true;
+        }
+    }
+
+    let mut docs = match parse_version_docs(client, options, module, version).await {
+        Some(docs) => docs,
+        None => {
+            tracing::error!("Unable to generate docs for {}@{}", module, version);
+            return false;
+        }
+    };
+
+    docs.tags = tags;
+
+    let directory = out_dir.join(module);
+    let path = directory.join(format!("{}.json", docs.version));
+    let result = fs::create_dir_all(&directory)
+        .and_then(|_| File::create(&path))
+        .map_err(OutputError::from)
+        .and_then(|file| {
+            output::write_docs(
+                OutputFormat::Json,
+                &docs,
+                &options.output_options,
+                BufWriter::new(file),
+            )
+        });
+
+    match result {
+        Ok(()) => {
+            tracing::debug!(
+                "Wrote docs for {}@{} to {}",
+                module,
+                docs.version,
+                path.display()
+            );
+            true
+        }
+        Err(e) => {
+            tracing::error!("Unable to write {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Prints the published versions of the module newest first, marking the latest with a `*`.
+async fn list_versions(client: &DenoModuleClient, module: &str) {
+    let versions = match client.fetch_versions_for_module(module).await {
+        Ok(versions) => versions,
+        Err(e @ FetchError::MetadataNotPresent) => {
+            return log_fetch_error(&format!("Module \"{}\" not found", module), &e)
+        }
+        Err(e) => return log_fetch_error("Unable to fetch versions", &e),
+    };
+
+    for version in &versions.versions {
+        if *version == versions.latest {
+            println!("{}*", version);
+        } else {
+            println!("{}", version);
+        }
+    }
+}
+
+/// Logs an error from fetching something, including the HTTP status code if there is one and how
+/// long to wait if the request was rate limited.
+fn log_fetch_error(context: &str, e: &FetchError) {
+    if let Some(retry_after) = e.retry_after() {
+        return tracing::error!(
+            "{}: {} (try again in {} seconds)",
+            context,
+            e,
+            retry_after.as_secs()
+        );
+    }
+
+    match e.http_status() {
+        Some(status) => tracing::error!("{}: {} (HTTP {})", context, e, status),
+        None => tracing::error!("{}: {}", context, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{parse_private_source, parse_source};
+
+    const KINDS_SOURCE: &str = r#"
+export function run(): void {}
+export class Server {}
+class Hidden {}
+export interface Handler {}
+export const port = 8000;
+export type Id = string;
+export enum Method {
+  Get,
+  Post,
+}
+export namespace Util {
+  export const version = 1;
+}
+"#;
+
+    fn names(nodes: &[DocNode]) -> Vec<&str> {
+        let mut names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[tokio::test]
+    async fn empty_file_fails_on_empty() {
+        let options =
+            Options::parse(vec!["example".to_string(), "--fail-on-empty".to_string()]).unwrap();
+        let nodes = parse_source("").await;
+
+        assert_eq!(empty_docs_error(&options, &nodes), Some(2));
+    }
+
+    #[tokio::test]
+    async fn empty_file_succeeds_without_fail_on_empty() {
+        let options = Options::parse(vec!["example".to_string()]).unwrap();
+        let nodes = parse_source("").await;
+
+        assert_eq!(empty_docs_error(&options, &nodes), None);
+    }
+
+    #[tokio::test]
+    async fn only_module_doc_fails_on_empty() {
+        let options =
+            Options::parse(vec!["example".to_string(), "--fail-on-empty".to_string()]).unwrap();
+        let mut nodes = parse_source(KINDS_SOURCE).await;
+        let mut module_doc = nodes[0].clone();
+        module_doc.kind = DocNodeKind::ModuleDoc;
+        nodes.push(module_doc);
+
+        assert_eq!(empty_docs_error(&options, &nodes), None);
+
+        retain_kind(&mut nodes, &DocNodeKind::Import);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(empty_docs_error(&options, &nodes), Some(2));
+    }
+
+    #[tokio::test]
+    async fn retain_kind_keeps_only_the_kind() {
+        let nodes = parse_source(KINDS_SOURCE).await;
+        let cases = [
+            (DocNodeKind::Function, "run"),
+            (DocNodeKind::Class, "Server"),
+            (DocNodeKind::Interface, "Handler"),
+            (DocNodeKind::Variable, "port"),
+            (DocNodeKind::TypeAlias, "Id"),
+            (DocNodeKind::Enum, "Method"),
+            (DocNodeKind::Namespace, "Util"),
+        ];
+
+        for (kind, name) in cases.iter() {
+            let mut kept = nodes.clone();
+            retain_kind(&mut kept, kind);
+            assert_eq!(names(&kept), vec![*name], "kept for {:?}", kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn retain_kind_keeps_private_symbols() {
+        let mut nodes = parse_private_source(KINDS_SOURCE).await;
+        retain_kind(&mut nodes, &DocNodeKind::Class);
+
+        assert_eq!(names(&nodes), vec!["Hidden", "Server"]);
+    }
+
+    #[test]
+    fn kind_composes_with_private() {
+        let options = Options::parse(
+            vec!["example", "--private", "--kind", "class"]
+                .into_iter()
+                .map(String::from),
+        )
         .unwrap();
-    log::debug!("Found {} doc items", res.len());
+
+        assert!(options.private);
+        assert_eq!(options.kind, Some(DocNodeKind::Class));
+    }
 }
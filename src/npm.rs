@@ -0,0 +1,286 @@
+//! Resolves `npm:` specifiers to the url of a file in the package on a CDN and loads it.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+
+use crate::fetch::{self, DenoModuleClient, FetchError};
+
+/// The registry package manifests are fetched from.
+const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+
+/// The CDN that files of npm packages are loaded from.
+const NPM_CDN_URL: &str = "https://unpkg.com";
+
+/// The file loaded when a package doesn't declare an entry point, matching Node's default.
+const DEFAULT_NPM_ENTRY: &str = "index.js";
+
+/// Resolves specifiers like `npm:lodash@4` or `npm:@scope/pkg@1.2.3/sub/path.js` to the url of
+/// the file on a CDN, and loads the file from there.
+///
+/// Resolutions are cached, so each specifier is only looked up in the registry once.
+#[derive(Debug)]
+pub struct NpmResolver {
+    client: DenoModuleClient,
+    resolved: Mutex<HashMap<String, String>>,
+}
+
+impl NpmResolver {
+    /// Creates a resolver that fetches manifests from the npm registry using the client.
+    pub fn new(client: DenoModuleClient) -> Self {
+        Self {
+            client,
+            resolved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolves the `npm:` specifier to the url of the file it refers to.
+    ///
+    /// Without a subpath this is the package's `types`, `typings`, `module` or `main` file, in
+    /// that order of preference. The version can be exact, a dist-tag, or a partial version like
+    /// `4` or `^4.17`, which resolves to the newest release it matches.
+    pub async fn resolve(&self, specifier: &str) -> Result<String, FetchError> {
+        if let Some(url) = self.resolved.lock().unwrap().get(specifier) {
+            return Ok(url.clone());
+        }
+
+        let npm_specifier = NpmSpecifier::parse(specifier)
+            .ok_or_else(|| FetchError::InvalidNpmSpecifier(specifier.to_string()))?;
+
+        tracing::debug!("Fetching npm manifest for {}.", npm_specifier.name);
+        let request = self.client.http().get(&format!(
+            "{}/{}",
+            NPM_REGISTRY_URL,
+            npm_specifier.name.replace('/', "%2f")
+        ));
+        let response = fetch::send(&self.client, request).await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => return Err(FetchError::MetadataNotPresent),
+            status if !status.is_success() => return Err(FetchError::UnexpectedStatus(status)),
+            _ => {}
+        }
+
+        let packument: Packument = response.json().await?;
+        let version = packument
+            .select_version(npm_specifier.version.unwrap_or("latest"))
+            .ok_or(FetchError::MetadataNotPresent)?;
+
+        let path = match npm_specifier.path {
+            Some(path) => path,
+            None => packument.versions[version].entry_point(),
+        };
+        let url = format!(
+            "{}/{}@{}/{}",
+            NPM_CDN_URL,
+            npm_specifier.name,
+            version,
+            path.trim_start_matches("./")
+        );
+
+        tracing::debug!("Resolved {} to {}", specifier, url);
+        self.resolved
+            .lock()
+            .unwrap()
+            .insert(specifier.to_string(), url.clone());
+
+        Ok(url)
+    }
+
+    /// Fetches the source of the file the `npm:` specifier refers to.
+    pub async fn load(&self, specifier: &str) -> Result<String, FetchError> {
+        let url = self.resolve(specifier).await?;
+
+        tracing::debug!("Fetching {} from {}.", specifier, url);
+        let response = fetch::send(&self.client, self.client.http().get(&url)).await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Err(FetchError::MetadataNotPresent),
+            status if !status.is_success() => Err(FetchError::UnexpectedStatus(status)),
+            _ => Ok(response.text().await?),
+        }
+    }
+
+    /// Resolves a relative import in a file of a package to the exact `npm:` specifier of the
+    /// file it imports, or [None] if the file importing it hasn't been resolved.
+    pub fn resolve_relative(&self, specifier: &str, referrer: &str) -> Option<String> {
+        let referrer_url = self.resolved.lock().unwrap().get(referrer)?.clone();
+        let url = Url::parse(&referrer_url).ok()?.join(specifier).ok()?;
+
+        // The path of a file on the CDN is the package, its version, then the path in it.
+        let path = url.as_str().strip_prefix(NPM_CDN_URL)?.strip_prefix('/')?;
+        Some(format!("npm:{}", path))
+    }
+}
+
+/// The parts of an `npm:` specifier.
+#[derive(Debug)]
+struct NpmSpecifier<'a> {
+    name: &'a str,
+    version: Option<&'a str>,
+    path: Option<&'a str>,
+}
+
+impl<'a> NpmSpecifier<'a> {
+    /// Parses a specifier like `npm:@scope/name@version/path`, where the version and path are
+    /// optional.
+    fn parse(specifier: &'a str) -> Option<Self> {
+        let rest = specifier.strip_prefix("npm:")?.trim_start_matches('/');
+        if rest.is_empty() {
+            return None;
+        }
+
+        // The name of a scoped package contains a `/` of its own.
+        let name_len = if rest.starts_with('@') {
+            let scope_len = rest.find('/')? + 1;
+            scope_len
+                + rest[scope_len..]
+                    .find('/')
+                    .unwrap_or(rest.len() - scope_len)
+        } else {
+            rest.find('/').unwrap_or(rest.len())
+        };
+        let (package, path) = rest.split_at(name_len);
+        let path = Some(path.trim_start_matches('/')).filter(|path| !path.is_empty());
+
+        // Skip the leading `@` of a scope when looking for the one before the version.
+        let (name, version) = match package.get(1..).and_then(|package| package.find('@')) {
+            Some(index) => (&package[..=index], Some(&package[index + 2..])),
+            None => (package, None),
+        };
+
+        if name.is_empty() || name.ends_with('/') {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            version: version.filter(|version| !version.is_empty()),
+            path,
+        })
+    }
+}
+
+/// The registry document describing every version of a package.
+#[derive(Debug, Deserialize)]
+struct Packument {
+    #[serde(rename = "dist-tags", default)]
+    dist_tags: HashMap<String, String>,
+    #[serde(default)]
+    versions: HashMap<String, NpmManifest>,
+}
+
+impl Packument {
+    /// Finds the published version the requested version refers to.
+    fn select_version(&self, requested: &str) -> Option<&str> {
+        if let Some((version, _)) = self.versions.get_key_value(requested) {
+            return Some(version);
+        }
+
+        if let Some(version) = self.dist_tags.get(requested) {
+            return self
+                .versions
+                .get_key_value(version)
+                .map(|(version, _)| version.as_str());
+        }
+
+        // `^` allows any newer release with the same major version, and `~` with the same minor.
+        let mut prefix = parse_version(requested.trim_start_matches(|c| c == '^' || c == '~'))?;
+        if requested.starts_with('^') {
+            prefix.truncate(1);
+        } else if requested.starts_with('~') {
+            prefix.truncate(2);
+        }
+
+        // Pre-releases aren't parsed, so they're never picked.
+        self.versions
+            .keys()
+            .filter_map(|version| Some((parse_version(version)?, version)))
+            .filter(|(parts, _)| parts.starts_with(&prefix))
+            .max()
+            .map(|(_, version)| version.as_str())
+    }
+}
+
+/// The manifest of a single version of a package.
+#[derive(Debug, Deserialize)]
+struct NpmManifest {
+    types: Option<String>,
+    typings: Option<String>,
+    module: Option<String>,
+    main: Option<String>,
+}
+
+impl NpmManifest {
+    /// Gets the file the package is loaded from, preferring type declarations.
+    fn entry_point(&self) -> &str {
+        self.types
+            .as_deref()
+            .or_else(|| self.typings.as_deref())
+            .or_else(|| self.module.as_deref())
+            .or_else(|| self.main.as_deref())
+            .unwrap_or(DEFAULT_NPM_ENTRY)
+    }
+}
+
+/// Parses the numeric parts of a version like `4.17.21`, or [None] if any part isn't a number.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(specifier: &str) -> Option<(&str, Option<&str>, Option<&str>)> {
+        NpmSpecifier::parse(specifier).map(|parsed| (parsed.name, parsed.version, parsed.path))
+    }
+
+    #[test]
+    fn parse_specifiers() {
+        assert_eq!(parse("npm:lodash"), Some(("lodash", None, None)));
+        assert_eq!(parse("npm:lodash@4"), Some(("lodash", Some("4"), None)));
+        assert_eq!(
+            parse("npm:lodash@4.17.21/fp.js"),
+            Some(("lodash", Some("4.17.21"), Some("fp.js")))
+        );
+        assert_eq!(parse("npm:@types/node"), Some(("@types/node", None, None)));
+        assert_eq!(
+            parse("npm:@scope/pkg@1.2.3/sub/path.js"),
+            Some(("@scope/pkg", Some("1.2.3"), Some("sub/path.js")))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_specifiers() {
+        assert_eq!(parse("lodash"), None);
+        assert_eq!(parse("npm:"), None);
+        assert_eq!(parse("npm:@scope"), None);
+    }
+
+    #[test]
+    fn select_version() {
+        let packument: Packument = serde_json::from_str(
+            r#"{
+                "dist-tags": { "latest": "4.17.21", "next": "5.0.0" },
+                "versions": {
+                    "3.10.1": {},
+                    "4.16.0": {},
+                    "4.17.20": {},
+                    "4.17.21": {},
+                    "5.0.0": {}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(packument.select_version("4.16.0"), Some("4.16.0"));
+        assert_eq!(packument.select_version("latest"), Some("4.17.21"));
+        assert_eq!(packument.select_version("next"), Some("5.0.0"));
+        assert_eq!(packument.select_version("4"), Some("4.17.21"));
+        assert_eq!(packument.select_version("^4.16"), Some("4.17.21"));
+        assert_eq!(packument.select_version("~4.16"), Some("4.16.0"));
+        assert_eq!(packument.select_version("6"), None);
+    }
+}
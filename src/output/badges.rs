@@ -0,0 +1,143 @@
+//! Writes shields.io badges describing the module.
+//!
+//! Only the version, export count and documentation coverage badges are written. The registry
+//! metadata this crate fetches has no license, supported Deno version or quality score, so there
+//! are no badges for those.
+
+use std::io::Write;
+
+use deno_doc::DocNodeKind;
+use serde::Serialize;
+
+use super::{ModuleDocs, OutputError};
+
+/// A badge that can be rendered by shields.io.
+#[derive(Debug, Serialize)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    pub color: String,
+    /// A link to the badge image on shields.io.
+    pub url: String,
+}
+
+impl Badge {
+    fn new(label: &str, message: String, color: &str) -> Self {
+        let url = format!(
+            "https://img.shields.io/badge/{}-{}-{}",
+            escape(label),
+            escape(&message),
+            color
+        );
+
+        Self {
+            label: label.to_string(),
+            message,
+            color: color.to_string(),
+            url,
+        }
+    }
+}
+
+/// Writes a JSON array of badges for the version of the module and how well it's documented.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let symbols: Vec<_> = docs
+        .nodes
+        .iter()
+        .filter(|node| !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import))
+        .collect();
+    let documented = symbols.iter().filter(|node| node.js_doc.is_some()).count();
+    let coverage = if symbols.is_empty() {
+        100
+    } else {
+        documented * 100 / symbols.len()
+    };
+    let coverage_color = match coverage {
+        80..=100 => "brightgreen",
+        50..=79 => "yellow",
+        _ => "red",
+    };
+
+    let badges = vec![
+        Badge::new(&docs.module_name, docs.version.clone(), "blue"),
+        Badge::new("exports", symbols.len().to_string(), "informational"),
+        Badge::new("docs", format!("{}%", coverage), coverage_color),
+    ];
+
+    serde_json::to_writer_pretty(&mut writer, &badges)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Escapes text for a path segment of a shields.io static badge, where `-` and `_` have special
+/// meaning.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '-' => escaped.push_str("--"),
+            '_' => escaped.push_str("__"),
+            ' ' => escaped.push('_'),
+            c if c.is_ascii_alphanumeric() || c == '.' || c == '~' => escaped.push(c),
+            c => {
+                let mut buffer = [0; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    escaped.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::test_util::module_docs;
+
+    const SOURCE: &str = r#"
+/** Adds two numbers. */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+export class Point {
+  x = 0;
+  y = 0;
+}
+"#;
+
+    #[tokio::test]
+    async fn writes_an_array_of_badges() {
+        let docs = module_docs(SOURCE).await;
+        let mut output = Vec::new();
+        write(&docs, &mut output).unwrap();
+        let badges: Vec<Value> = serde_json::from_slice(&output).unwrap();
+
+        let messages: Vec<(&str, &str)> = badges
+            .iter()
+            .map(|badge| {
+                (
+                    badge["label"].as_str().unwrap(),
+                    badge["message"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            vec![("example", "1.0.0"), ("exports", "2"), ("docs", "50%")]
+        );
+        assert_eq!(
+            badges[2]["url"],
+            "https://img.shields.io/badge/docs-50%25-yellow"
+        );
+    }
+}
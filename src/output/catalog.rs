@@ -0,0 +1,76 @@
+//! Writes a compact JSON catalog of the module, suitable for indexing in a registry mirror.
+
+use std::io::Write;
+
+use deno_doc::{DocNode, DocNodeKind};
+use serde::Serialize;
+
+use super::{ModuleDocs, OutputError};
+
+/// An entry describing a module in a catalog.
+#[derive(Debug, Serialize)]
+pub struct CatalogEntry<'a> {
+    pub module: &'a str,
+    pub version: &'a str,
+    pub description: Option<&'a str>,
+    pub exported_symbols: Vec<CatalogSymbol<'a>>,
+    pub entry_point: &'a str,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub tags: &'a [String],
+}
+
+/// A symbol exported by a module in a catalog.
+#[derive(Debug, Serialize)]
+pub struct CatalogSymbol<'a> {
+    pub name: &'a str,
+    pub kind: DocNodeKind,
+    /// The first paragraph of the symbol's JSDoc.
+    pub summary: Option<&'a str>,
+}
+
+impl<'a> From<&'a DocNode> for CatalogSymbol<'a> {
+    fn from(node: &'a DocNode) -> Self {
+        let summary = node
+            .js_doc
+            .as_deref()
+            .and_then(|js_doc| js_doc.trim().split("\n\n").next())
+            .map(str::trim)
+            .filter(|summary| !summary.is_empty());
+
+        Self {
+            name: &node.name,
+            kind: node.kind.clone(),
+            summary,
+        }
+    }
+}
+
+/// Writes the catalog entry of the module as JSON.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let exported_symbols = docs
+        .nodes
+        .iter()
+        .filter(|node| !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import))
+        .map(CatalogSymbol::from)
+        .collect();
+
+    let entry = CatalogEntry {
+        module: &docs.module_name,
+        version: &docs.version,
+        description: docs.module_description(),
+        exported_symbols,
+        entry_point: &docs.entry_point,
+        file_count: docs.file_count,
+        total_bytes: docs.total_bytes,
+        tags: &docs.tags,
+    };
+
+    serde_json::to_writer(&mut writer, &entry)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
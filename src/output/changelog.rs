@@ -0,0 +1,203 @@
+//! Formats the changes between two versions of a module as a changelog.
+
+use std::{io::Write, str::FromStr};
+
+use serde::Serialize;
+
+use super::OutputError;
+
+/// The exported symbols that changed between two versions of a module.
+#[derive(Debug, Default, Serialize)]
+pub struct Changes {
+    pub module_name: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// The symbols whose kind or signature changed.
+    pub changed: Vec<String>,
+    /// The symbols where only the summary of their JSDoc changed.
+    pub docs_changed: Vec<String>,
+}
+
+/// A format a changelog can be written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChangelogFormat {
+    /// The changes as a JSON object.
+    #[default]
+    Json,
+    /// Markdown following the conventions of <https://keepachangelog.com>.
+    KeepAChangelog,
+    /// A commit message per change following <https://www.conventionalcommits.org>.
+    Conventional,
+}
+
+impl FromStr for ChangelogFormat {
+    type Err = OutputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "keepachangelog" => Ok(Self::KeepAChangelog),
+            "conventional" => Ok(Self::Conventional),
+            _ => Err(OutputError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Writes the changes to the writer in the specified changelog format.
+pub fn write<W>(changes: &Changes, format: ChangelogFormat, writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    match format {
+        ChangelogFormat::Json => write_json(changes, writer),
+        ChangelogFormat::KeepAChangelog => write_keep_a_changelog(changes, writer),
+        ChangelogFormat::Conventional => write_conventional(changes, writer),
+    }
+}
+
+fn write_json<W>(changes: &Changes, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    serde_json::to_writer_pretty(&mut writer, changes)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_keep_a_changelog<W>(changes: &Changes, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    writeln!(writer, "# Changelog")?;
+    writeln!(writer)?;
+    writeln!(writer, "## [{}]", changes.to_version)?;
+
+    let changed: Vec<&String> = changes
+        .changed
+        .iter()
+        .chain(&changes.docs_changed)
+        .collect();
+    let sections = [
+        ("Added", changes.added.iter().collect()),
+        ("Changed", changed),
+        ("Removed", changes.removed.iter().collect()),
+    ];
+
+    for (heading, names) in sections.iter() {
+        if names.is_empty() {
+            continue;
+        }
+
+        writeln!(writer)?;
+        writeln!(writer, "### {}", heading)?;
+        writeln!(writer)?;
+
+        for name in names.iter() {
+            writeln!(writer, "- `{}`", name)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_conventional<W>(changes: &Changes, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    for name in &changes.added {
+        writeln!(writer, "feat: add `{}`", name)?;
+    }
+
+    // Changing or removing an exported symbol can break anything depending on it.
+    for name in &changes.changed {
+        writeln!(writer, "feat!: change `{}`", name)?;
+    }
+
+    for name in &changes.removed {
+        writeln!(writer, "feat!: remove `{}`", name)?;
+    }
+
+    for name in &changes.docs_changed {
+        writeln!(writer, "docs: update `{}`", name)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn changes() -> Changes {
+        Changes {
+            module_name: "example".to_string(),
+            from_version: "1.0.0".to_string(),
+            to_version: "2.0.0".to_string(),
+            added: vec!["Added".to_string()],
+            removed: vec!["removed".to_string()],
+            changed: vec!["retyped".to_string()],
+            docs_changed: vec!["documented".to_string()],
+        }
+    }
+
+    fn write_to_string(format: ChangelogFormat) -> String {
+        let mut output = Vec::new();
+        write(&changes(), format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn json() {
+        let changes: Value = serde_json::from_str(&write_to_string(ChangelogFormat::Json)).unwrap();
+
+        assert_eq!(changes["to_version"], "2.0.0");
+        assert_eq!(changes["added"], serde_json::json!(["Added"]));
+        assert_eq!(changes["removed"], serde_json::json!(["removed"]));
+        assert_eq!(changes["changed"], serde_json::json!(["retyped"]));
+        assert_eq!(changes["docs_changed"], serde_json::json!(["documented"]));
+    }
+
+    #[test]
+    fn keep_a_changelog() {
+        assert_eq!(
+            write_to_string(ChangelogFormat::KeepAChangelog),
+            "# Changelog\n\n## [2.0.0]\n\n### Added\n\n- `Added`\n\n### Changed\n\n- `retyped`\n- `documented`\n\n### Removed\n\n- `removed`\n"
+        );
+    }
+
+    #[test]
+    fn keep_a_changelog_skips_empty_sections() {
+        let changes = Changes {
+            to_version: "2.0.0".to_string(),
+            added: vec!["Added".to_string()],
+            ..Changes::default()
+        };
+        let mut output = Vec::new();
+        write(&changes, ChangelogFormat::KeepAChangelog, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "# Changelog\n\n## [2.0.0]\n\n### Added\n\n- `Added`\n"
+        );
+    }
+
+    #[test]
+    fn conventional_only_marks_signature_changes_as_breaking() {
+        assert_eq!(
+            write_to_string(ChangelogFormat::Conventional),
+            "feat: add `Added`\nfeat!: change `retyped`\nfeat!: remove `removed`\ndocs: update `documented`\n"
+        );
+    }
+
+    #[test]
+    fn default_is_json() {
+        assert_eq!(ChangelogFormat::default(), ChangelogFormat::Json);
+    }
+}
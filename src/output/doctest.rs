@@ -0,0 +1,145 @@
+//! Extracts the `@example` blocks of each node into standalone TypeScript files, running them
+//! with Deno if it's available.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use tempfile::Builder;
+
+use super::{ModuleDocs, OutputError};
+
+/// The environment variable pointing to the Deno binary examples are run with.
+const DENO_PATH_VAR: &str = "DENO_PATH";
+
+/// Writes each example to a file in a new temporary directory, reporting whether it passed when
+/// it's run with the Deno binary at `DENO_PATH`, or just where it was written if that isn't set.
+///
+/// The directory is removed after the examples are run, and kept if they weren't. The report
+/// starts with the module and its description.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let directory = Builder::new()
+        .prefix(&format!("{}-{}-doctest-", docs.module_name, docs.version))
+        .tempdir()?;
+
+    writeln!(writer, "{}@{}", docs.module_name, docs.version)?;
+    if let Some(description) = docs.module_description() {
+        writeln!(writer, "{}", description.trim())?;
+    }
+
+    let deno = env::var_os(DENO_PATH_VAR).map(PathBuf::from);
+    let mut passed = 0;
+    let mut failed = 0;
+    // How many nodes with each name have been seen, so overloads don't share file names.
+    let mut overloads: HashMap<&str, usize> = HashMap::new();
+
+    for node in &docs.nodes {
+        let overload = overloads.entry(&node.name).or_insert(0);
+        let overload_index = *overload;
+        *overload += 1;
+
+        let js_doc = match &node.js_doc {
+            Some(js_doc) => js_doc,
+            None => continue,
+        };
+
+        for (index, example) in extract_examples(js_doc).into_iter().enumerate() {
+            let name = format!("{}#{}.{}", node.name, overload_index, index);
+            let path = directory
+                .path()
+                .join(format!("{}_{}_{}.ts", node.name, overload_index, index));
+            fs::write(&path, example)?;
+
+            match &deno {
+                Some(deno) if run_example(deno, &path)? => {
+                    passed += 1;
+                    writeln!(writer, "pass {} ({})", name, path.display())?;
+                }
+                Some(_) => {
+                    failed += 1;
+                    writeln!(writer, "FAIL {} ({})", name, path.display())?;
+                }
+                None => writeln!(writer, "wrote {} to {}", name, path.display())?,
+            }
+        }
+    }
+
+    if deno.is_some() {
+        writeln!(writer, "{} passed, {} failed", passed, failed)?;
+    } else {
+        // The examples are the only output when they aren't run, so they have to outlive this.
+        directory.into_path();
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Runs the example with `deno run`, returning whether it exited successfully.
+fn run_example(deno: &Path, path: &Path) -> Result<bool, OutputError> {
+    let status = Command::new(deno)
+        .arg("run")
+        .arg(path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    Ok(status.success())
+}
+
+/// Extracts the code of every `@example` tag in the JSDoc, which lasts until the next tag.
+///
+/// If the example contains a fenced code block only the code inside of it is kept, so any
+/// description of the example isn't run.
+fn extract_examples(js_doc: &str) -> Vec<String> {
+    let mut examples = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+
+    for line in js_doc.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("@example") {
+            examples.extend(current.take().map(|lines| example_code(&lines)));
+            // Anything on the same line as the tag is a caption rather than code.
+            current = Some(Vec::new());
+        } else if trimmed.starts_with('@') {
+            examples.extend(current.take().map(|lines| example_code(&lines)));
+        } else if let Some(lines) = &mut current {
+            lines.push(line);
+        }
+    }
+
+    examples.extend(current.map(|lines| example_code(&lines)));
+    examples.retain(|example| !example.trim().is_empty());
+    examples
+}
+
+/// Gets the code of an example from its lines, preferring the contents of a fenced code block.
+fn example_code(lines: &[&str]) -> String {
+    let fence_start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("```"));
+
+    let code = match fence_start {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| line.trim_start().starts_with("```"))
+                .map(|end| start + 1 + end)
+                .unwrap_or(lines.len());
+            &lines[start + 1..end]
+        }
+        None => lines,
+    };
+
+    let mut code = code.join("\n");
+    code.push('\n');
+    code
+}
@@ -0,0 +1,172 @@
+//! Writes an MDX page for each exported symbol along with a sidebar, for Docusaurus 3.x sites.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use deno_doc::{DocNode, DocNodeKind};
+
+use super::{markdown::signature, ModuleDocs, OutputError};
+use crate::doc_node::{DocNodeExt, SeeRef};
+
+/// The name of the page with the module description, which Docusaurus uses for the directory.
+const INDEX_PAGE: &str = "index";
+
+/// Writes an index page with the module description, a page for each exported symbol and a
+/// `sidebar.ts` listing them to the directory, writing the path of each created file to the
+/// writer.
+///
+/// Overloads of a function share the page of the function.
+pub fn write<W>(docs: &ModuleDocs, out_dir: Option<&Path>, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let out_dir = out_dir.ok_or(OutputError::MissingOutDir)?;
+    fs::create_dir_all(out_dir)?;
+
+    let mut names: Vec<&str> = Vec::new();
+    for node in &docs.nodes {
+        if !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import)
+            && !names.contains(&node.name.as_str())
+        {
+            names.push(&node.name);
+        }
+    }
+
+    let index_path = out_dir.join(format!("{}.mdx", INDEX_PAGE));
+    fs::write(&index_path, render_index(docs))?;
+    writeln!(writer, "{}", index_path.display())?;
+
+    for (position, name) in names.iter().enumerate() {
+        let nodes: Vec<&DocNode> = docs
+            .nodes
+            .iter()
+            .filter(|node| node.name == *name)
+            .collect();
+        let path = out_dir.join(format!("{}.mdx", name));
+        // The index page is the first in the sidebar.
+        fs::write(&path, render_page(name, &nodes, position + 2))?;
+        writeln!(writer, "{}", path.display())?;
+    }
+
+    let sidebar_path = write_sidebar(docs, out_dir, &names)?;
+    writeln!(writer, "{}", sidebar_path.display())?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Renders the index page of the module, with its description.
+fn render_index(docs: &ModuleDocs) -> String {
+    let description = docs.module_description().map(str::trim).unwrap_or("");
+    let summary = description
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+
+    let mut page = String::new();
+    page.push_str("---\n");
+    page.push_str(&format!("title: {}\n", quote(&docs.module_name)));
+    page.push_str(&format!("description: {}\n", quote(summary)));
+    page.push_str("sidebar_position: 1\n");
+    page.push_str("---\n\n");
+    page.push_str(&format!(
+        "# {}@{}\n",
+        escape_mdx(&docs.module_name),
+        docs.version
+    ));
+
+    if !description.is_empty() {
+        page.push_str(&format!("\n{}\n", escape_mdx(description)));
+    }
+
+    page
+}
+
+/// Renders the page of a symbol, which has a section for each of the nodes declaring it.
+fn render_page(name: &str, nodes: &[&DocNode], position: usize) -> String {
+    let description = nodes
+        .iter()
+        .find_map(|node| node.js_doc.as_deref())
+        .and_then(|js_doc| js_doc.lines().map(str::trim).find(|line| !line.is_empty()))
+        .unwrap_or("");
+
+    let mut page = String::new();
+    page.push_str("---\n");
+    page.push_str(&format!("title: {}\n", quote(name)));
+    page.push_str(&format!("description: {}\n", quote(description)));
+    page.push_str(&format!("sidebar_position: {}\n", position));
+    page.push_str("---\n\n");
+    page.push_str(&format!("# `{}`\n", name));
+
+    for node in nodes {
+        page.push_str(&format!("\n```ts\n{}\n```\n", signature(node)));
+
+        if let Some(js_doc) = &node.js_doc {
+            page.push_str(&format!("\n{}\n", escape_mdx(js_doc.trim())));
+        }
+
+        let see_also = node.see_also();
+        if !see_also.is_empty() {
+            let links: Vec<String> = see_also.iter().map(link).collect();
+            page.push_str(&format!("\n**See also:** {}\n", links.join(", ")));
+        }
+
+        page.push_str(&format!(
+            "\n_Defined in {}:{}_\n",
+            escape_mdx(&node.location.filename),
+            node.location.line
+        ));
+    }
+
+    page
+}
+
+/// Writes a `sidebar.ts` listing the page of every symbol, returning its path.
+fn write_sidebar(
+    docs: &ModuleDocs,
+    out_dir: &Path,
+    names: &[&str],
+) -> Result<PathBuf, OutputError> {
+    let mut sidebar = String::new();
+    sidebar
+        .push_str("import type { SidebarsConfig } from \"@docusaurus/plugin-content-docs\";\n\n");
+    sidebar.push_str("const sidebars: SidebarsConfig = {\n");
+    sidebar.push_str(&format!("  {}: [\n", quote(&docs.module_name)));
+    sidebar.push_str(&format!("    {},\n", quote(INDEX_PAGE)));
+    for name in names {
+        sidebar.push_str(&format!("    {},\n", quote(name)));
+    }
+    sidebar.push_str("  ],\n");
+    sidebar.push_str("};\n\n");
+    sidebar.push_str("export default sidebars;\n");
+
+    let path = out_dir.join("sidebar.ts");
+    fs::write(&path, sidebar)?;
+    Ok(path)
+}
+
+/// Creates a link to the url or to the page of the symbol that's referenced.
+fn link(see_ref: &SeeRef) -> String {
+    if see_ref.is_url {
+        format!("[{0}]({0})", see_ref.target)
+    } else {
+        format!("[`{0}`](./{0})", see_ref.target)
+    }
+}
+
+/// Creates a short TypeScript-like signature for the node.
+/// Quotes the text as a double-quoted string, which is valid in both YAML and TypeScript.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Escapes the characters that MDX would otherwise treat as JSX.
+fn escape_mdx(text: &str) -> String {
+    text.replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace('<', "&lt;")
+}
@@ -0,0 +1,126 @@
+//! Writes GraphQL SDL `type` definitions for the exported interfaces.
+
+use std::{collections::HashSet, io::Write};
+
+use deno_doc::ts_type::TsTypeDef;
+
+use super::{ModuleDocs, OutputError};
+
+/// The scalar used for TypeScript types that don't have a GraphQL equivalent.
+const JSON_SCALAR: &str = "JSON";
+
+/// Writes a `type` for every exported interface, with TypeScript scalars mapped to GraphQL's.
+///
+/// Types that can't be represented in GraphQL, like unions of primitives or references to types
+/// that aren't exported interfaces, are mapped to a `JSON` scalar which is declared if used.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let interfaces: HashSet<&str> = docs
+        .nodes
+        .iter()
+        .filter(|node| node.interface_def.is_some())
+        .map(|node| node.name.as_str())
+        .collect();
+    let mut uses_json = false;
+    let mut definitions = Vec::new();
+
+    for node in &docs.nodes {
+        let interface = match &node.interface_def {
+            Some(interface) => interface,
+            None => continue,
+        };
+
+        let mut definition = String::new();
+        if let Some(js_doc) = &node.js_doc {
+            definition.push_str(&description(js_doc, ""));
+        }
+        definition.push_str(&format!("type {} {{\n", node.name));
+
+        for property in &interface.properties {
+            let mut field_type = match &property.ts_type {
+                Some(ts_type) => graphql_type(ts_type, &interfaces),
+                None => None,
+            }
+            .unwrap_or_else(|| {
+                uses_json = true;
+                JSON_SCALAR.to_string()
+            });
+
+            if !property.optional {
+                field_type.push('!');
+            }
+
+            if let Some(js_doc) = &property.js_doc {
+                definition.push_str(&description(js_doc, "  "));
+            }
+            definition.push_str(&format!("  {}: {}\n", property.name, field_type));
+        }
+
+        definition.push_str("}\n");
+        definitions.push(definition);
+    }
+
+    if let Some(module_description) = docs.module_description() {
+        for line in module_description.lines() {
+            writeln!(writer, "# {}", line)?;
+        }
+        writeln!(writer)?;
+    }
+
+    if uses_json {
+        writeln!(writer, "scalar {}", JSON_SCALAR)?;
+        writeln!(writer)?;
+    }
+
+    writeln!(writer, "{}", definitions.join("\n").trim_end())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Maps the TypeScript type to a GraphQL type, returning [None] if it doesn't have one.
+fn graphql_type(ts_type: &TsTypeDef, interfaces: &HashSet<&str>) -> Option<String> {
+    if let Some(keyword) = &ts_type.keyword {
+        return match keyword.as_str() {
+            "string" => Some("String".into()),
+            "number" => Some("Float".into()),
+            "bigint" => Some("Int".into()),
+            "boolean" => Some("Boolean".into()),
+            _ => None,
+        };
+    }
+
+    if let Some(element) = &ts_type.array {
+        return Some(format!("[{}]", graphql_type(element, interfaces)?));
+    }
+
+    if let Some(type_ref) = &ts_type.type_ref {
+        let type_name = type_ref.type_name.as_str();
+
+        // `Array<T>` is the same as `T[]`.
+        if type_name == "Array" {
+            let element = type_ref.type_params.as_ref()?.first()?;
+            return Some(format!("[{}]", graphql_type(element, interfaces)?));
+        }
+
+        if interfaces.contains(type_name) {
+            return Some(type_name.to_string());
+        }
+    }
+
+    None
+}
+
+/// Formats the doc comment as a GraphQL block string description.
+fn description(js_doc: &str, indent: &str) -> String {
+    format!(
+        "{}\"\"\"\n{}{}\n{}\"\"\"\n",
+        indent,
+        indent,
+        js_doc
+            .replace("\"\"\"", "\\\"\"\"")
+            .replace('\n', &format!("\n{}", indent)),
+        indent
+    )
+}
@@ -0,0 +1,75 @@
+use std::io::Write;
+
+use serde_json::Value;
+
+use super::{ModuleDocs, OutputError};
+
+/// Writes the doc nodes as a single JSON array, pretty-printed unless `compact` is set.
+///
+/// The module description isn't written separately since it's already one of the nodes.
+pub fn write<W>(docs: &ModuleDocs, compact: bool, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let nodes = (0..docs.nodes.len())
+        .map(|index| docs.node_to_json(index))
+        .collect::<serde_json::Result<Vec<Value>>>()?;
+
+    if compact {
+        serde_json::to_writer(&mut writer, &nodes)?;
+    } else {
+        serde_json::to_writer_pretty(&mut writer, &nodes)?;
+    }
+
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::module_docs;
+
+    const SOURCE: &str = r#"
+/** Adds two numbers. */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+export class Point {
+  x = 0;
+  y = 0;
+}
+"#;
+
+    fn write_to_string(docs: &ModuleDocs, compact: bool) -> String {
+        let mut output = Vec::new();
+        write(docs, compact, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn nodes_round_trip() {
+        let docs = module_docs(SOURCE).await;
+        let nodes: Vec<Value> = serde_json::from_str(&write_to_string(&docs, false)).unwrap();
+
+        assert_eq!(nodes.len(), docs.nodes.len());
+        assert!(nodes.iter().any(|node| node["name"] == "add"));
+        assert!(nodes.iter().any(|node| node["name"] == "Point"));
+    }
+
+    #[tokio::test]
+    async fn compact_is_a_single_line() {
+        let docs = module_docs(SOURCE).await;
+        let compact = write_to_string(&docs, true);
+        let pretty = write_to_string(&docs, false);
+
+        assert_eq!(compact.lines().count(), 1);
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(
+            serde_json::from_str::<Vec<Value>>(&compact).unwrap(),
+            serde_json::from_str::<Vec<Value>>(&pretty).unwrap()
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use serde_json::json;
+
+use super::{ModuleDocs, OutputError};
+
+/// The YAML document separator written between each of the nodes.
+const SEPARATOR: &str = "---";
+
+/// Writes each doc node as its own pretty-printed JSON object, separated by a `---` line.
+///
+/// Nodes are written one at a time so large modules don't need to be serialized into a single
+/// buffer before anything is output. If the module has a description, it's written as the first
+/// object.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let mut is_first = true;
+
+    if let Some(description) = docs.module_description() {
+        let header = json!({ "module_description": description });
+        serde_json::to_writer_pretty(&mut writer, &header)?;
+        writeln!(writer)?;
+        is_first = false;
+    }
+
+    for index in 0..docs.nodes.len() {
+        if !is_first {
+            writeln!(writer, "{}", SEPARATOR)?;
+        }
+        is_first = false;
+
+        serde_json::to_writer_pretty(&mut writer, &docs.node_to_json(index)?)?;
+        writeln!(writer)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Writes a strict JSON Schema with a definition for each exported interface.
+
+use std::{collections::HashSet, io::Write};
+
+use deno_doc::ts_type::TsTypeDef;
+use serde_json::{json, Map, Value};
+
+use super::{ModuleDocs, OutputError};
+
+/// The JSON Schema draft the definitions are written for.
+const SCHEMA_DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Writes a schema with a definition for every exported interface.
+///
+/// Every object disallows properties that aren't declared, properties that aren't optional are
+/// required, and required `string` properties can't be empty. Types that can't be represented in
+/// JSON Schema accept any value.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let interfaces: HashSet<&str> = docs
+        .nodes
+        .iter()
+        .filter(|node| node.interface_def.is_some())
+        .map(|node| node.name.as_str())
+        .collect();
+    let mut definitions = Map::new();
+
+    for node in &docs.nodes {
+        let interface = match &node.interface_def {
+            Some(interface) => interface,
+            None => continue,
+        };
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for property in &interface.properties {
+            let mut schema = match &property.ts_type {
+                Some(ts_type) => schema_for_type(ts_type, &interfaces),
+                None => json!({}),
+            };
+
+            if !property.optional {
+                if is_string(&schema) {
+                    schema["minLength"] = 1.into();
+                }
+                required.push(Value::from(property.name.as_str()));
+            }
+
+            if let (Some(js_doc), Value::Object(map)) = (&property.js_doc, &mut schema) {
+                map.insert("description".into(), js_doc.trim().into());
+            }
+            properties.insert(property.name.clone(), schema);
+        }
+
+        let mut definition = json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        });
+        if let Some(js_doc) = &node.js_doc {
+            definition["description"] = js_doc.trim().into();
+        }
+        definitions.insert(node.name.clone(), definition);
+    }
+
+    let mut schema = json!({
+        "$schema": SCHEMA_DRAFT,
+        "title": docs.module_name,
+        "definitions": definitions,
+    });
+    if let Some(module_description) = docs.module_description() {
+        schema["description"] = module_description.trim().into();
+    }
+
+    serde_json::to_writer_pretty(&mut writer, &schema)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Maps the TypeScript type to a schema, which accepts anything if the type can't be represented.
+fn schema_for_type(ts_type: &TsTypeDef, interfaces: &HashSet<&str>) -> Value {
+    if let Some(keyword) = &ts_type.keyword {
+        return match keyword.as_str() {
+            "string" => json!({ "type": "string" }),
+            "number" => json!({ "type": "number" }),
+            "bigint" => json!({ "type": "integer" }),
+            "boolean" => json!({ "type": "boolean" }),
+            "null" => json!({ "type": "null" }),
+            _ => json!({}),
+        };
+    }
+
+    if let Some(element) = &ts_type.array {
+        return json!({ "type": "array", "items": schema_for_type(element, interfaces) });
+    }
+
+    if let Some(types) = &ts_type.union {
+        let schemas: Vec<Value> = types
+            .iter()
+            .map(|ts_type| schema_for_type(ts_type, interfaces))
+            .collect();
+        return json!({ "anyOf": schemas });
+    }
+
+    if let Some(type_ref) = &ts_type.type_ref {
+        let type_name = type_ref.type_name.as_str();
+
+        // `Array<T>` is the same as `T[]`.
+        if type_name == "Array" {
+            let items = match type_ref
+                .type_params
+                .as_ref()
+                .and_then(|params| params.first())
+            {
+                Some(element) => schema_for_type(element, interfaces),
+                None => json!({}),
+            };
+            return json!({ "type": "array", "items": items });
+        }
+
+        if interfaces.contains(type_name) {
+            return json!({ "$ref": format!("#/definitions/{}", type_name) });
+        }
+    }
+
+    json!({})
+}
+
+/// Checks if the schema only accepts strings.
+fn is_string(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("string")
+}
@@ -0,0 +1,31 @@
+use std::io::Write;
+
+use serde_json::Value;
+
+use super::{ModuleDocs, OutputError};
+
+/// Writes the doc nodes as a single JSON array, with the `byte_start` and `byte_end` of each
+/// node's declaration in its source file when they're known.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let mut nodes = Vec::with_capacity(docs.nodes.len());
+
+    for index in 0..docs.nodes.len() {
+        let mut value = docs.node_to_json(index)?;
+
+        if let (Value::Object(map), Some((start, end))) = (&mut value, docs.byte_ranges.get(&index))
+        {
+            map.insert("byte_start".into(), (*start).into());
+            map.insert("byte_end".into(), (*end).into());
+        }
+
+        nodes.push(value);
+    }
+
+    serde_json::to_writer_pretty(&mut writer, &nodes)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Writes a manual page in `roff` markup, which can be displayed with `man ./output.1`.
+
+use std::io::Write;
+
+use deno_doc::{function::FunctionDef, DocNode};
+
+use super::{format_params, ModuleDocs, OutputError};
+use crate::doc_node::DocNodeExt;
+
+/// Writes the docs as a section 1 manual page describing the exported functions.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let functions: Vec<(&DocNode, &FunctionDef)> = docs
+        .nodes
+        .iter()
+        .filter_map(|node| node.function_def.as_ref().map(|def| (node, def)))
+        .collect();
+    let description = docs.module_description();
+
+    writeln!(
+        writer,
+        ".TH \"{}\" 1 \"\" \"{}\" \"{} manual\"",
+        escape(&docs.module_name.to_uppercase()),
+        escape(&docs.version),
+        escape(&docs.module_name)
+    )?;
+
+    writeln!(writer, ".SH NAME")?;
+    match description.and_then(|description| description.lines().next()) {
+        Some(summary) => writeln!(
+            writer,
+            "{} \\- {}",
+            escape(&docs.module_name),
+            escape(summary)
+        )?,
+        None => writeln!(writer, "{}", escape(&docs.module_name))?,
+    }
+
+    writeln!(writer, ".SH SYNOPSIS")?;
+    for (node, def) in &functions {
+        writeln!(writer, ".B {}", escape(&node.name))?;
+        writeln!(writer, "({})", escape(&format_params(&def.params)))?;
+        writeln!(writer, ".br")?;
+    }
+
+    if let Some(description) = description {
+        writeln!(writer, ".SH DESCRIPTION")?;
+        write_paragraphs(&mut writer, description)?;
+    }
+
+    if !functions.is_empty() {
+        writeln!(writer, ".SH OPTIONS")?;
+
+        for (node, def) in &functions {
+            writeln!(writer, ".TP")?;
+            writeln!(
+                writer,
+                ".B {}({})",
+                escape(&node.name),
+                escape(&format_params(&def.params))
+            )?;
+
+            match &node.js_doc {
+                Some(js_doc) => write_paragraphs(&mut writer, js_doc)?,
+                None => writeln!(writer, "Undocumented.")?,
+            }
+
+            let see_also: Vec<String> = node
+                .see_also()
+                .into_iter()
+                .map(|see_ref| see_ref.target)
+                .collect();
+            if !see_also.is_empty() {
+                writeln!(writer, ".sp")?;
+                writeln!(writer, "See also: {}", escape(&see_also.join(", ")))?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the text with blank lines turned into paragraph breaks.
+fn write_paragraphs<W>(writer: &mut W, text: &str) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            writeln!(writer, ".PP")?;
+        } else {
+            writeln!(writer, "{}", escape(line.trim()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Escapes text so it isn't interpreted as `roff` requests or escapes.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+
+    // Lines starting with a control character would be treated as a request.
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
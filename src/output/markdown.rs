@@ -0,0 +1,186 @@
+//! Writes a GitHub-Flavored Markdown document describing each exported symbol.
+
+use std::io::Write;
+
+use deno_doc::{DocNode, DocNodeKind};
+
+use super::{format_params, ModuleDocs, OutputError};
+use crate::doc_node::{DocNodeExt, SeeRef};
+
+/// Writes the docs as a Markdown document.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let title = format!("{}@{}", docs.module_name, docs.version);
+    let markdown = render_to_markdown(&title, &docs.nodes);
+    writer.write_all(markdown.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Renders a document with a heading for the module, followed by a section for each exported
+/// symbol with its signature and JSDoc.
+///
+/// Overloads of a function share a section, with a signature for each of them. The module
+/// description is written under the heading. The nodes don't know which module they're from,
+/// so the heading is given by the caller.
+pub fn render_to_markdown(module_name: &str, nodes: &[DocNode]) -> String {
+    let mut markdown = format!("# {}\n", module_name);
+    if let Some(description) = DocNode::module_doc(nodes) {
+        markdown.push_str(&format!("\n{}\n", description.trim()));
+    }
+
+    let mut names: Vec<&str> = Vec::new();
+    for node in nodes {
+        if !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import)
+            && !names.contains(&node.name.as_str())
+        {
+            names.push(&node.name);
+        }
+    }
+
+    for name in names {
+        markdown.push_str(&format!("\n## `{}`\n", name));
+
+        for node in DocNode::overload_index(nodes, name) {
+            markdown.push_str(&format!("\n```ts\n{}\n```\n", signature(node)));
+
+            if let Some(js_doc) = &node.js_doc {
+                markdown.push_str(&format!("\n{}\n", js_doc.trim()));
+            }
+
+            let see_also = node.see_also();
+            if !see_also.is_empty() {
+                let links: Vec<String> = see_also.iter().map(link).collect();
+                markdown.push_str(&format!("\n**See also:** {}\n", links.join(", ")));
+            }
+        }
+    }
+
+    markdown
+}
+
+/// Creates a link to the url or to the section of the symbol that's referenced.
+fn link(see_ref: &SeeRef) -> String {
+    if see_ref.is_url {
+        format!("<{}>", see_ref.target)
+    } else {
+        // GitHub creates the anchors of headings from their text in lowercase.
+        format!("[`{}`](#{})", see_ref.target, see_ref.target.to_lowercase())
+    }
+}
+
+/// Creates the TypeScript declaration of the node, without any bodies or members.
+pub(super) fn signature(node: &DocNode) -> String {
+    if let Some(def) = &node.function_def {
+        let mut signature = format!("function {}({})", node.name, format_params(&def.params));
+        if let Some(return_type) = &def.return_type {
+            signature.push_str(&format!(": {}", return_type.repr));
+        }
+        return signature;
+    }
+
+    if let Some(def) = &node.type_alias_def {
+        return format!("type {} = {}", node.name, def.ts_type.repr);
+    }
+
+    let inherits_from = node.inherits_from();
+    match node.kind {
+        DocNodeKind::Class => {
+            let mut signature = format!("class {}", node.name);
+            if let Some(class) = &node.class_def {
+                if let Some(extends) = &class.extends {
+                    signature.push_str(&format!(" extends {}", extends));
+                }
+                if !class.implements.is_empty() {
+                    let implements: Vec<&str> = class
+                        .implements
+                        .iter()
+                        .map(|ts_type| ts_type.repr.as_str())
+                        .collect();
+                    signature.push_str(&format!(" implements {}", implements.join(", ")));
+                }
+            }
+            signature
+        }
+        DocNodeKind::Interface if !inherits_from.is_empty() => format!(
+            "interface {} extends {}",
+            node.name,
+            inherits_from.join(", ")
+        ),
+        _ => format!(
+            "{} {}",
+            format!("{:?}", node.kind).to_lowercase(),
+            node.name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_source;
+
+    const SOURCE: &str = r#"
+/** Adds two numbers. */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+/** Something with an area. */
+export interface Shape {
+  area(): number;
+}
+
+export class Point implements Shape {
+  area(): number {
+    return 0;
+  }
+}
+
+/** The id of a shape. */
+export type Id = string;
+"#;
+
+    /// Finds the section of the symbol, without its heading.
+    fn section<'a>(markdown: &'a str, name: &str) -> &'a str {
+        let heading = format!("`{}`\n", name);
+        markdown
+            .split("\n## ")
+            .find_map(|section| section.strip_prefix(heading.as_str()))
+            .unwrap_or_else(|| panic!("no section for {} in:\n{}", name, markdown))
+    }
+
+    #[tokio::test]
+    async fn renders_each_symbol() {
+        let nodes = parse_source(SOURCE).await;
+        let markdown = render_to_markdown("example", &nodes);
+
+        assert!(markdown.starts_with("# example\n"));
+
+        let add = section(&markdown, "add");
+        assert!(add.contains("```ts\nfunction add("));
+        assert!(add.contains("): number\n```\n"));
+        assert!(add.contains("\nAdds two numbers.\n"));
+
+        let shape = section(&markdown, "Shape");
+        assert!(shape.contains("```ts\ninterface Shape\n```\n"));
+        assert!(shape.contains("\nSomething with an area.\n"));
+
+        let id = section(&markdown, "Id");
+        assert!(id.contains("```ts\ntype Id = string\n```\n"));
+        assert!(id.contains("\nThe id of a shape.\n"));
+    }
+
+    #[tokio::test]
+    async fn symbols_without_js_doc_have_no_description() {
+        let nodes = parse_source(SOURCE).await;
+        let markdown = render_to_markdown("example", &nodes);
+
+        assert_eq!(
+            section(&markdown, "Point"),
+            "\n```ts\nclass Point implements Shape\n```\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Writes Mermaid.js diagrams, which can be embedded in GitHub Markdown.
+
+use std::{collections::HashMap, io::Write};
+
+use deno_doc::{ts_type::TsTypeDef, DocNode};
+
+use super::{format_params, ModuleDocs, OutputError};
+
+/// Writes a `classDiagram` of the classes and interfaces followed by a `graph LR` of the imports
+/// between files.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    if let Some(description) = docs.module_description() {
+        for line in description.lines() {
+            writeln!(writer, "%% {}", line)?;
+        }
+    }
+
+    write_class_diagram(&docs.nodes, &mut writer)?;
+    writeln!(writer)?;
+    write_dependency_graph(&docs.nodes, &mut writer)?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_class_diagram<W>(nodes: &[DocNode], writer: &mut W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    writeln!(writer, "classDiagram")?;
+
+    for node in nodes {
+        let name = escape(&node.name);
+
+        if let Some(class) = &node.class_def {
+            writeln!(writer, "    class {} {{", name)?;
+            for property in &class.properties {
+                writeln!(
+                    writer,
+                    "        +{} {}",
+                    type_name(property.ts_type.as_ref()),
+                    escape(&property.name)
+                )?;
+            }
+            for method in &class.methods {
+                writeln!(
+                    writer,
+                    "        +{}({}) {}",
+                    escape(&method.name),
+                    escape(&format_params(&method.function_def.params)),
+                    type_name(method.function_def.return_type.as_ref())
+                )?;
+            }
+            writeln!(writer, "    }}")?;
+
+            if let Some(extends) = &class.extends {
+                writeln!(writer, "    {} <|-- {}", escape(extends), name)?;
+            }
+            for implements in &class.implements {
+                writeln!(writer, "    {} <|.. {}", escape(&implements.repr), name)?;
+            }
+        } else if let Some(interface) = &node.interface_def {
+            writeln!(writer, "    class {} {{", name)?;
+            writeln!(writer, "        <<interface>>")?;
+            for property in &interface.properties {
+                writeln!(
+                    writer,
+                    "        +{} {}",
+                    type_name(property.ts_type.as_ref()),
+                    escape(&property.name)
+                )?;
+            }
+            for method in &interface.methods {
+                writeln!(
+                    writer,
+                    "        +{}({}) {}",
+                    escape(&method.name),
+                    escape(&format_params(&method.params)),
+                    type_name(method.return_type.as_ref())
+                )?;
+            }
+            writeln!(writer, "    }}")?;
+
+            for extends in &interface.extends {
+                writeln!(writer, "    {} <|-- {}", escape(&extends.repr), name)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_dependency_graph<W>(nodes: &[DocNode], writer: &mut W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    writeln!(writer, "graph LR")?;
+
+    // Mermaid node ids can't contain most of the characters in a specifier, so each file is
+    // given an id and labelled with its specifier.
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+
+    for node in nodes {
+        let import = match &node.import_def {
+            Some(import) => import,
+            None => continue,
+        };
+
+        let from = file_id(&mut ids, &node.location.filename, writer)?;
+        let to = file_id(&mut ids, &import.src, writer)?;
+
+        writeln!(writer, "    f{} --> f{}", from, to)?;
+    }
+
+    Ok(())
+}
+
+/// Gets the id of the file in the dependency graph, declaring it the first time it's seen.
+fn file_id<'a, W>(
+    ids: &mut HashMap<&'a str, usize>,
+    file: &'a str,
+    writer: &mut W,
+) -> Result<usize, OutputError>
+where
+    W: Write,
+{
+    if let Some(&id) = ids.get(file) {
+        return Ok(id);
+    }
+
+    let id = ids.len();
+    ids.insert(file, id);
+    writeln!(writer, "    f{}[\"{}\"]", id, file.replace('"', "#quot;"))?;
+
+    Ok(id)
+}
+
+/// Gets the name of the type, or nothing if it isn't known.
+fn type_name(ts_type: Option<&TsTypeDef>) -> String {
+    ts_type
+        .map(|ts_type| escape(&ts_type.repr))
+        .unwrap_or_default()
+}
+
+/// Replaces characters that can't be used in a class diagram, using Mermaid's `~T~` syntax for
+/// generics.
+fn escape(text: &str) -> String {
+    text.replace('<', "~")
+        .replace('>', "~")
+        .replace('{', "(")
+        .replace('}', ")")
+}
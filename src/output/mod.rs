@@ -0,0 +1,238 @@
+pub mod badges;
+pub mod catalog;
+pub mod changelog;
+pub mod doctest;
+pub mod docusaurus;
+pub mod graphql;
+pub mod json;
+pub mod json_lines_pretty;
+pub mod json_schema_strict;
+pub mod json_with_locations;
+pub mod man;
+pub mod markdown;
+pub mod mermaid;
+pub mod notion;
+pub mod pot;
+pub mod typedoc_theme;
+pub mod vscode_extension;
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::Args;
+use deno_doc::{params::ParamDef, DocNode};
+use serde_json::Value;
+use thiserror::Error;
+
+use self::changelog::ChangelogFormat;
+use crate::doc_node::DocNodeExt;
+
+/// A format that parsed doc nodes can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Every doc node in a single JSON array.
+    Json,
+    /// Every doc node as its own pretty-printed JSON object, separated by `---`.
+    JsonLinesPretty,
+    /// An HTML page rendered with a TypeDoc theme, or a minimal built-in one.
+    TypedocTheme,
+    /// A Unix manual page describing the exported functions.
+    Man,
+    /// A gettext template for translating the JSDoc of each node.
+    Pot,
+    /// Mermaid.js class and dependency diagrams.
+    Mermaid,
+    /// GraphQL SDL types for the exported interfaces.
+    Graphql,
+    /// The module description and shields.io badges describing the module, as JSON.
+    Badges,
+    /// The `@example` blocks of each node as TypeScript files, run with Deno if it's available.
+    Doctest,
+    /// A compact JSON catalog of the module for indexing.
+    Catalog,
+    /// An MDX page for each exported symbol and a sidebar, for a Docusaurus site.
+    Docusaurus,
+    /// A JSON Schema for the exported interfaces that rejects anything they don't declare.
+    JsonSchemaStrict,
+    /// A GitHub-Flavored Markdown document with a section for each exported symbol.
+    Markdown,
+    /// Notion blocks for each exported symbol, for the `blocks.children.append` endpoint.
+    NotionBlocks,
+    /// The JSON output with the byte range of each node's declaration in its source file.
+    JsonWithLocations,
+    /// The `package.json` of a VS Code extension with a TypeScript server plugin for each
+    /// exported class.
+    VscodeManifest,
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json-lines-pretty" => Ok(Self::JsonLinesPretty),
+            "typedoc-theme" => Ok(Self::TypedocTheme),
+            "man" => Ok(Self::Man),
+            "pot" => Ok(Self::Pot),
+            "mermaid" => Ok(Self::Mermaid),
+            "graphql" => Ok(Self::Graphql),
+            "badges" => Ok(Self::Badges),
+            "doctest" => Ok(Self::Doctest),
+            "catalog" => Ok(Self::Catalog),
+            "docusaurus" => Ok(Self::Docusaurus),
+            "json-schema-strict" => Ok(Self::JsonSchemaStrict),
+            "markdown" => Ok(Self::Markdown),
+            "notion-blocks" => Ok(Self::NotionBlocks),
+            "json-with-locations" => Ok(Self::JsonWithLocations),
+            "vscode-manifest" => Ok(Self::VscodeManifest),
+            _ => Err(OutputError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Checks if the format is one of the JSON formats.
+    pub fn is_json(self) -> bool {
+        matches!(
+            self,
+            Self::Json | Self::JsonLinesPretty | Self::JsonWithLocations
+        )
+    }
+}
+
+/// The parsed doc nodes of a module along with any extra analysis done on them.
+#[derive(Debug, Default)]
+pub struct ModuleDocs {
+    pub module_name: String,
+    pub version: String,
+    pub nodes: Vec<DocNode>,
+    /// The path of the file the docs were parsed from.
+    pub entry_point: String,
+    /// How many files are in the archive of the module.
+    pub file_count: usize,
+    /// The total size of the files in the archive of the module in bytes.
+    pub total_bytes: u64,
+    /// The tags of the module in the registry, like `cli`.
+    pub tags: Vec<String>,
+    /// The cyclomatic complexity of function nodes, keyed by the index of the node.
+    pub complexity: HashMap<usize, usize>,
+    /// The byte range of each node's declaration in its source file, keyed by the index of the
+    /// node.
+    pub byte_ranges: HashMap<usize, (usize, usize)>,
+}
+
+impl ModuleDocs {
+    /// Gets the module-level doc comment, which formats with a place for it write separately from
+    /// the nodes.
+    pub fn module_description(&self) -> Option<&str> {
+        DocNode::module_doc(&self.nodes)
+    }
+
+    /// Serializes the node at the index into JSON, including any analysis done on it.
+    pub fn node_to_json(&self, index: usize) -> serde_json::Result<Value> {
+        let node = &self.nodes[index];
+        let mut value = serde_json::to_value(node)?;
+
+        if let Value::Object(map) = &mut value {
+            if let Some(complexity) = self.complexity.get(&index) {
+                map.insert("complexity".into(), (*complexity).into());
+            }
+
+            let see_also = node.see_also();
+            if !see_also.is_empty() {
+                map.insert("see_also".into(), serde_json::to_value(see_also)?);
+            }
+
+            let type_params = node.type_params();
+            if !type_params.is_empty() {
+                map.insert("type_params".into(), serde_json::to_value(type_params)?);
+            }
+
+            if node.function_def.is_some() {
+                let overloads: Vec<_> = DocNode::overload_index(&self.nodes, &node.name)
+                    .into_iter()
+                    .filter_map(|node| node.function_def.as_ref())
+                    .collect();
+
+                if overloads.len() > 1 {
+                    map.insert("overloads".into(), serde_json::to_value(overloads)?);
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// Options that only apply to some of the output formats.
+#[derive(Debug, Default, Args)]
+pub struct OutputOptions {
+    /// The path to a TypeDoc theme used by the `typedoc-theme` format.
+    #[arg(long)]
+    pub theme: Option<PathBuf>,
+    /// The format changelogs between versions are written in.
+    #[arg(long, default_value = "json")]
+    pub changelog_format: ChangelogFormat,
+    /// Whether JSON is written without any whitespace, used by the `json` format.
+    #[arg(long)]
+    pub compact: bool,
+    /// The directory formats that write several files, like `docusaurus`, write them to.
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+/// Writes the module docs to the writer in the specified format.
+pub fn write_docs<W>(
+    format: OutputFormat,
+    docs: &ModuleDocs,
+    options: &OutputOptions,
+    writer: W,
+) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    match format {
+        OutputFormat::Json => json::write(docs, options.compact, writer),
+        OutputFormat::JsonLinesPretty => json_lines_pretty::write(docs, writer),
+        OutputFormat::TypedocTheme => typedoc_theme::write(docs, options.theme.as_deref(), writer),
+        OutputFormat::Man => man::write(docs, writer),
+        OutputFormat::Pot => pot::write(docs, writer),
+        OutputFormat::Mermaid => mermaid::write(docs, writer),
+        OutputFormat::Graphql => graphql::write(docs, writer),
+        OutputFormat::Badges => badges::write(docs, writer),
+        OutputFormat::Doctest => doctest::write(docs, writer),
+        OutputFormat::Catalog => catalog::write(docs, writer),
+        OutputFormat::Docusaurus => docusaurus::write(docs, options.out_dir.as_deref(), writer),
+        OutputFormat::JsonSchemaStrict => json_schema_strict::write(docs, writer),
+        OutputFormat::Markdown => markdown::write(docs, writer),
+        OutputFormat::NotionBlocks => notion::write(docs, writer),
+        OutputFormat::JsonWithLocations => json_with_locations::write(docs, writer),
+        OutputFormat::VscodeManifest => vscode_extension::write(docs, writer),
+    }
+}
+
+/// Formats the parameters of a function, separated by commas.
+fn format_params(params: &[ParamDef]) -> String {
+    params
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Error)]
+pub enum OutputError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unknown output format \"{0}\"")]
+    UnknownFormat(String),
+    #[error("this output format requires --out-dir")]
+    MissingOutDir,
+}
@@ -0,0 +1,124 @@
+//! Writes Notion block JSON that can be sent to the `blocks.children.append` endpoint.
+
+use std::io::Write;
+
+use deno_doc::{DocNode, DocNodeKind};
+use serde_json::{json, Value};
+
+use super::{markdown::signature, ModuleDocs, OutputError};
+use crate::doc_node::DocNodeExt;
+
+/// The most characters the Notion API accepts in a single rich text object.
+const MAX_TEXT_LENGTH: usize = 2000;
+
+/// Writes a JSON array with a `blocks.children.append` payload for the module and each exported
+/// symbol.
+///
+/// The module's payload has a `heading_1` block with its name and a `paragraph` block with its
+/// description. Each symbol's payload has a `heading_2` block with the name of the symbol, then a `code` block with
+/// the signature and a `paragraph` block with the JSDoc of each of its declarations.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let mut names: Vec<&str> = Vec::new();
+    for node in &docs.nodes {
+        if !matches!(node.kind, DocNodeKind::ModuleDoc | DocNodeKind::Import)
+            && !names.contains(&node.name.as_str())
+        {
+            names.push(&node.name);
+        }
+    }
+
+    let mut module_blocks = vec![block(
+        "heading_1",
+        json!({ "rich_text": rich_text(&format!("{}@{}", docs.module_name, docs.version)) }),
+    )];
+    if let Some(description) = docs.module_description() {
+        module_blocks.push(block(
+            "paragraph",
+            json!({ "rich_text": rich_text(description.trim()) }),
+        ));
+    }
+
+    let mut payloads = vec![json!({ "children": module_blocks })];
+    payloads.extend(names.into_iter().map(|name| {
+        let nodes = DocNode::overload_index(&docs.nodes, name);
+        json!({ "children": symbol_blocks(name, &nodes) })
+    }));
+
+    serde_json::to_writer_pretty(&mut writer, &payloads)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Creates the blocks describing a symbol and each of its declarations.
+fn symbol_blocks(name: &str, nodes: &[&DocNode]) -> Vec<Value> {
+    let mut blocks = vec![block("heading_2", json!({ "rich_text": rich_text(name) }))];
+
+    for node in nodes {
+        blocks.push(block(
+            "code",
+            json!({
+                "rich_text": rich_text(&signature(node)),
+                "language": "typescript",
+            }),
+        ));
+
+        if let Some(js_doc) = &node.js_doc {
+            blocks.push(block(
+                "paragraph",
+                json!({ "rich_text": rich_text(js_doc.trim()) }),
+            ));
+        }
+
+        let see_also = node.see_also();
+        if !see_also.is_empty() {
+            let mut text = rich_text("See also: ");
+            for (index, see_ref) in see_also.iter().enumerate() {
+                if index > 0 {
+                    text.extend(rich_text(", "));
+                }
+
+                // Only urls can be linked, since symbols don't have a page to link to.
+                let link = if see_ref.is_url {
+                    json!({ "url": see_ref.target })
+                } else {
+                    Value::Null
+                };
+                text.push(json!({
+                    "type": "text",
+                    "text": { "content": see_ref.target, "link": link },
+                }));
+            }
+            blocks.push(block("paragraph", json!({ "rich_text": text })));
+        }
+    }
+
+    blocks
+}
+
+/// Creates a block of the type, with the content under the key of the type as Notion expects.
+fn block(block_type: &str, content: Value) -> Value {
+    json!({
+        "object": "block",
+        "type": block_type,
+        block_type: content,
+    })
+}
+
+/// Creates the rich text objects for plain text, split into as many as needed to stay under the
+/// length limit.
+fn rich_text(text: &str) -> Vec<Value> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(MAX_TEXT_LENGTH)
+        .map(|chunk| {
+            json!({
+                "type": "text",
+                "text": { "content": chunk.iter().collect::<String>() },
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Writes a gettext Portable Object Template containing the JSDoc of each node.
+
+use std::io::Write;
+
+use super::{ModuleDocs, OutputError};
+
+/// Writes every JSDoc description as a `msgid`, with identical descriptions merged into a
+/// single entry referencing each of their locations.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    // Keeps the order the descriptions first appeared in so the template is stable.
+    let mut entries: Vec<(&str, Vec<String>)> = Vec::new();
+
+    for node in &docs.nodes {
+        let js_doc = match node.js_doc.as_deref().map(str::trim) {
+            Some(js_doc) if !js_doc.is_empty() => js_doc,
+            _ => continue,
+        };
+        let reference = format!("{}:{}", node.location.filename, node.location.line);
+
+        match entries.iter_mut().find(|(msgid, _)| *msgid == js_doc) {
+            Some((_, references)) => references.push(reference),
+            None => entries.push((js_doc, vec![reference])),
+        }
+    }
+
+    writeln!(writer, "# Translation template for {}.", docs.module_name)?;
+    writeln!(writer, "msgid \"\"")?;
+    writeln!(writer, "msgstr \"\"")?;
+    writeln!(
+        writer,
+        "\"Project-Id-Version: {} {}\\n\"",
+        escape(&docs.module_name),
+        escape(&docs.version)
+    )?;
+    writeln!(writer, "\"MIME-Version: 1.0\\n\"")?;
+    writeln!(writer, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+    writeln!(writer, "\"Content-Transfer-Encoding: 8bit\\n\"")?;
+
+    let module_description = docs.module_description().map(str::trim);
+
+    for (msgid, references) in entries {
+        writeln!(writer)?;
+
+        if Some(msgid) == module_description {
+            writeln!(writer, "#. module_description")?;
+        }
+
+        for reference in references {
+            writeln!(writer, "#: {}", reference)?;
+        }
+
+        write_msgid(&mut writer, msgid)?;
+        writeln!(writer, "msgstr \"\"")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the `msgid`, splitting multi-line strings into a string per line.
+fn write_msgid<W>(writer: &mut W, msgid: &str) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    if !msgid.contains('\n') {
+        writeln!(writer, "msgid \"{}\"", escape(msgid))?;
+        return Ok(());
+    }
+
+    writeln!(writer, "msgid \"\"")?;
+
+    let mut lines = msgid.lines().peekable();
+    while let Some(line) = lines.next() {
+        let newline = if lines.peek().is_some() { "\\n" } else { "" };
+        writeln!(writer, "\"{}{}\"", escape(line), newline)?;
+    }
+
+    Ok(())
+}
+
+/// Escapes the text to be used in a quoted PO string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\t', "\\t")
+}
@@ -0,0 +1,219 @@
+//! Renders the docs with a TypeDoc HTML theme.
+//!
+//! Rendering with a theme requires Node.js and `npx` to be on the `PATH`, as the docs are
+//! converted to TypeDoc's JSON format and rendered by running TypeDoc with the theme loaded as a
+//! plugin. If Node.js isn't available, no theme was provided, or rendering fails, a minimal
+//! built-in HTML page is written instead.
+
+use std::{fs, io::Write, path::Path, process::Command};
+
+use deno_doc::{DocNode, DocNodeKind};
+use serde_json::{json, Value};
+use tempfile::Builder;
+
+use super::{ModuleDocs, OutputError};
+use crate::doc_node::DocNodeExt;
+
+/// Writes the docs as an HTML page, rendered with the theme if possible.
+///
+/// TypeDoc renders a whole site, so only the index page of it is written to the writer.
+pub fn write<W>(docs: &ModuleDocs, theme: Option<&Path>, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    if let Some(theme) = theme {
+        match render_with_theme(docs, theme) {
+            Ok(Some(html)) => {
+                writer.write_all(html.as_bytes())?;
+                writer.flush()?;
+                return Ok(());
+            }
+            Ok(None) => tracing::warn!("Unable to render with TypeDoc, using the built-in theme"),
+            Err(e) => tracing::warn!(
+                "Unable to render with TypeDoc, using the built-in theme: {}",
+                e
+            ),
+        }
+    }
+
+    write_minimal_html(docs, writer)
+}
+
+/// Renders the docs by running TypeDoc through Node.js, returning [None] if TypeDoc couldn't be
+/// run or didn't succeed.
+fn render_with_theme(docs: &ModuleDocs, theme: &Path) -> Result<Option<String>, OutputError> {
+    let directory = Builder::new()
+        .prefix(&format!("{}-{}-typedoc-", docs.module_name, docs.version))
+        .tempdir()?;
+    let json_path = directory.path().join("typedoc.json");
+    let out_path = directory.path().join("out");
+
+    fs::write(&json_path, serde_json::to_vec(&project_json(docs))?)?;
+
+    let status = Command::new("npx")
+        .arg("typedoc")
+        .arg("--entryPointStrategy")
+        .arg("merge")
+        .arg(&json_path)
+        .arg("--plugin")
+        .arg(theme)
+        .arg("--out")
+        .arg(&out_path)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => {
+            Ok(Some(fs::read_to_string(out_path.join("index.html"))?))
+        }
+        Ok(status) => {
+            tracing::debug!("TypeDoc exited with {}", status);
+            Ok(None)
+        }
+        Err(e) => {
+            tracing::debug!("Unable to run TypeDoc through npx: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Converts the docs into a TypeDoc project reflection.
+fn project_json(docs: &ModuleDocs) -> Value {
+    let children: Vec<Value> = docs
+        .nodes
+        .iter()
+        .filter_map(reflection_kind)
+        .enumerate()
+        .map(|(index, (node, kind))| {
+            let mut child = json!({
+                "id": index + 1,
+                "name": node.name,
+                "kind": kind,
+                "flags": {},
+            });
+
+            if let Some(js_doc) = &node.js_doc {
+                child["comment"] = json!({ "shortText": js_doc });
+            }
+
+            child
+        })
+        .collect();
+
+    let mut project = json!({
+        "id": 0,
+        "name": docs.module_name,
+        "kind": 1,
+        "flags": {},
+        "children": children,
+    });
+
+    if let Some(description) = docs.module_description() {
+        project["comment"] = json!({ "shortText": description });
+    }
+
+    project
+}
+
+/// Gets the TypeDoc reflection kind of the node, if it has one.
+fn reflection_kind(node: &DocNode) -> Option<(&DocNode, u32)> {
+    let kind = match node.kind {
+        DocNodeKind::Namespace => 0x4,
+        DocNodeKind::Enum => 0x8,
+        DocNodeKind::Variable => 0x20,
+        DocNodeKind::Function => 0x40,
+        DocNodeKind::Class => 0x80,
+        DocNodeKind::Interface => 0x100,
+        DocNodeKind::TypeAlias => 0x400000,
+        _ => return None,
+    };
+
+    Some((node, kind))
+}
+
+/// Writes the built-in HTML page listing every doc node.
+fn write_minimal_html<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let title = escape_html(&format!("{}@{}", docs.module_name, docs.version));
+
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html>")?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>{}</title>", title)?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<h1>{}</h1>", title)?;
+
+    if let Some(description) = docs.module_description() {
+        writeln!(
+            writer,
+            "<p class=\"module_description\">{}</p>",
+            escape_html(description)
+        )?;
+    }
+
+    for node in &docs.nodes {
+        if let DocNodeKind::ModuleDoc = node.kind {
+            continue;
+        }
+
+        writeln!(writer, "<section id=\"{}\">", escape_html(&node.name))?;
+        writeln!(
+            writer,
+            "<h2><code>{}</code> <small>{:?}</small></h2>",
+            escape_html(&node.name),
+            node.kind
+        )?;
+
+        if let Some(js_doc) = &node.js_doc {
+            writeln!(writer, "<pre>{}</pre>", escape_html(js_doc))?;
+        }
+
+        let see_also = node.see_also();
+        if !see_also.is_empty() {
+            let links: Vec<String> = see_also
+                .iter()
+                .map(|see_ref| {
+                    let href = if see_ref.is_url {
+                        see_ref.target.clone()
+                    } else {
+                        format!("#{}", see_ref.target)
+                    };
+                    format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(&href),
+                        escape_html(&see_ref.target)
+                    )
+                })
+                .collect();
+            writeln!(writer, "<p>See also: {}</p>", links.join(", "))?;
+        }
+
+        writeln!(writer, "</section>")?;
+    }
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Escapes the characters that have special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,82 @@
+//! Writes the `package.json` of a VS Code extension contributing TypeScript server plugins.
+
+use std::io::Write;
+
+use deno_doc::DocNodeKind;
+use serde::Serialize;
+
+use super::{ModuleDocs, OutputError};
+
+/// The oldest version of VS Code that supports `typescriptServerPlugins` contributions.
+const VSCODE_ENGINE: &str = "^1.30.0";
+
+/// The fields of an extension manifest describing the module and its plugins.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionManifest<'a> {
+    pub name: String,
+    pub display_name: &'a str,
+    pub description: Option<&'a str>,
+    pub version: &'a str,
+    pub engines: Engines,
+    pub contributes: Contributes<'a>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Engines {
+    pub vscode: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contributes<'a> {
+    pub typescript_server_plugins: Vec<ServerPlugin<'a>>,
+}
+
+/// A plugin loaded by the TypeScript server, named after an exported class.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerPlugin<'a> {
+    pub name: &'a str,
+    /// Whether the plugin is also loaded when the workspace's own TypeScript is used.
+    pub enable_for_workspace_type_script_versions: bool,
+}
+
+/// Writes a manifest with a `typescriptServerPlugins` contribution for each exported class.
+pub fn write<W>(docs: &ModuleDocs, mut writer: W) -> Result<(), OutputError>
+where
+    W: Write,
+{
+    let mut plugins: Vec<ServerPlugin> = Vec::new();
+    for node in &docs.nodes {
+        // Overloads and re-exports can list the same class more than once.
+        if node.kind == DocNodeKind::Class && !plugins.iter().any(|p| p.name == node.name) {
+            plugins.push(ServerPlugin {
+                name: &node.name,
+                enable_for_workspace_type_script_versions: true,
+            });
+        }
+    }
+
+    let manifest = ExtensionManifest {
+        // Extension names have to be lowercase, like npm package names.
+        name: docs.module_name.to_lowercase(),
+        display_name: &docs.module_name,
+        description: docs
+            .module_description()
+            .and_then(|description| description.trim().lines().next()),
+        // Versions have to be semver, which doesn't allow the `v` prefix of many tags.
+        version: docs.version.trim_start_matches('v'),
+        engines: Engines {
+            vscode: VSCODE_ENGINE,
+        },
+        contributes: Contributes {
+            typescript_server_plugins: plugins,
+        },
+    };
+
+    serde_json::to_writer_pretty(&mut writer, &manifest)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use deno_doc_info_generator::deno_archive::LoadEvent;
+
+/// Writes the load events as a Chrome trace, which can be opened in `chrome://tracing`.
+pub fn write_chrome_trace(events: &[LoadEvent], path: &Path) -> io::Result<()> {
+    let trace: Vec<Value> = events
+        .iter()
+        .map(|event| {
+            json!({
+                "ph": "X",
+                "name": event.specifier,
+                "ts": event.start.as_micros() as u64,
+                "dur": event.duration.as_micros() as u64,
+                "pid": 1,
+                "tid": 1,
+            })
+        })
+        .collect();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &trace)?;
+    writer.flush()
+}
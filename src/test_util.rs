@@ -0,0 +1,41 @@
+//! Helpers shared by the tests of the binary.
+
+use std::fs;
+
+use deno_doc::{DocNode, DocParser};
+
+use crate::{local_loader::LocalFileSystemLoader, output::ModuleDocs};
+
+/// Parses the doc nodes of a module with the source, which is written to a temporary directory.
+pub async fn parse_source(source: &str) -> Vec<DocNode> {
+    parse(source, false).await
+}
+
+/// Parses the doc nodes of a module with the source like [parse_source], including the symbols
+/// that aren't exported.
+pub async fn parse_private_source(source: &str) -> Vec<DocNode> {
+    parse(source, true).await
+}
+
+async fn parse(source: &str, private: bool) -> Vec<DocNode> {
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(directory.path().join("mod.ts"), source).unwrap();
+
+    let loader = LocalFileSystemLoader::new(directory.path()).unwrap();
+    let entry_point = loader.root().join("mod.ts");
+    let doc_parser = DocParser::new(Box::new(loader), private);
+    doc_parser
+        .parse(&entry_point.to_string_lossy())
+        .await
+        .unwrap()
+}
+
+/// Parses the source into the docs of version `1.0.0` of a module named `example`.
+pub async fn module_docs(source: &str) -> ModuleDocs {
+    ModuleDocs {
+        module_name: "example".to_string(),
+        version: "1.0.0".to_string(),
+        nodes: parse_source(source).await,
+        ..ModuleDocs::default()
+    }
+}
@@ -0,0 +1,30 @@
+//! The results of generating docs for a module as a library.
+
+use std::io;
+
+use deno_doc::{DocError, DocNode};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::fetch::FetchError;
+
+/// The doc nodes of a version of a module, from [crate::generate_doc_info].
+#[derive(Debug, Clone, Serialize)]
+pub struct DocInfo {
+    pub module_name: String,
+    /// The version the docs were generated for, with `latest` resolved to the actual version.
+    pub version: String,
+    pub nodes: Vec<DocNode>,
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateError {
+    #[error("{0}")]
+    Fetch(#[from] FetchError),
+    #[error("{0}")]
+    Doc(#[from] DocError),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("archive has no files")]
+    EmptyArchive,
+}
@@ -0,0 +1,85 @@
+//! Tests generating docs through the library with a client that sends its requests to a mock
+//! proxy instead of the registry.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use deno_doc_info_generator::{generate_doc_info, FetchError, GenerateError};
+use reqwest::{Client, Proxy};
+
+/// The request a client makes to a proxy to reach the registry over HTTPS.
+const REGISTRY_CONNECT: &str = "CONNECT cdn.deno.land:443 HTTP/1.1";
+
+/// Starts a proxy that refuses every request, returning a client that sends its requests through
+/// it and the request lines the proxy has received.
+fn refusing_proxy() -> (Client, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let received = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            received
+                .lock()
+                .unwrap()
+                .push(request_line.trim_end().to_string());
+
+            // The headers end with an empty line.
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                header.clear();
+            }
+
+            stream
+                .write_all(
+                    b"HTTP/1.1 502 Bad Gateway\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                )
+                .unwrap();
+        }
+    });
+
+    let client = Client::builder()
+        .proxy(Proxy::all(&format!("http://{}", address)).unwrap())
+        .build()
+        .unwrap();
+    (client, requests)
+}
+
+#[tokio::test]
+async fn latest_version_is_fetched() {
+    let (client, requests) = refusing_proxy();
+
+    let result = generate_doc_info(&client, "example", None).await;
+
+    assert!(matches!(
+        result,
+        Err(GenerateError::Fetch(FetchError::HTTP(_)))
+    ));
+    assert_eq!(requests.lock().unwrap().as_slice(), [REGISTRY_CONNECT]);
+}
+
+#[tokio::test]
+async fn version_is_fetched() {
+    let (client, requests) = refusing_proxy();
+
+    let result = generate_doc_info(&client, "example", Some("1.0.0")).await;
+
+    assert!(matches!(
+        result,
+        Err(GenerateError::Fetch(FetchError::HTTP(_)))
+    ));
+    assert_eq!(requests.lock().unwrap().as_slice(), [REGISTRY_CONNECT]);
+}